@@ -0,0 +1,46 @@
+use std::{ error::Error, time::SystemTime };
+use crate::FileRef;
+
+
+
+pub struct CachedFile {
+	file:FileRef,
+	contents:String,
+	modified:Option<SystemTime>
+}
+impl CachedFile {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new cache on the file. The file is only read on the first `get`.
+	pub fn new(file:&FileRef) -> CachedFile {
+		CachedFile {
+			file: file.clone(),
+			contents: String::new(),
+			modified: None
+		}
+	}
+
+
+
+	/* USAGE METHODS */
+
+	/// Get the contents of the file. Only reads the file if its modification time changed since the last read, otherwise returns the cached contents.
+	pub fn get(&mut self) -> Result<&str, Box<dyn Error>> {
+		let modified:SystemTime = self.file.get_time_modification()?;
+		if self.modified != Some(modified) {
+			self.contents = self.file.read()?;
+			self.modified = Some(modified);
+		}
+		Ok(&self.contents)
+	}
+
+
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the file being cached.
+	pub fn file(&self) -> &FileRef {
+		&self.file
+	}
+}
\ No newline at end of file
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+	use std::time::{ Duration, SystemTime };
+	use crate::{ CachedFile, FileRef, unit_test_support::{ Syscall, TempFile, count_syscalls } };
+
+
+
+	#[test]
+	fn test_cached_file_rereads_on_change() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let time_modify:SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+		temp_file_ref.write("first".to_string()).unwrap();
+		temp_file_ref.set_time_modification(time_modify).unwrap();
+
+		let mut cache:CachedFile = CachedFile::new(&temp_file_ref);
+		assert_eq!(cache.get().unwrap(), "first");
+
+		// Same modification time, so the cached contents are kept.
+		temp_file_ref.write("second".to_string()).unwrap();
+		temp_file_ref.set_time_modification(time_modify).unwrap();
+		assert_eq!(count_syscalls(Syscall::Stat, || assert_eq!(cache.get().unwrap(), "first")), 1);
+
+		// Changed modification time, so the file is read again.
+		temp_file_ref.touch().unwrap();
+		assert_eq!(cache.get().unwrap(), "second");
+	}
+
+	#[test]
+	fn test_cached_file_missing_file() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let mut cache:CachedFile = CachedFile::new(&FileRef::new(temp_file.path()));
+		assert!(cache.get().is_err());
+	}
+}
\ No newline at end of file
@@ -1,26 +1,48 @@
-use std::{ error::Error, ffi::OsStr, iter::once, os::windows::ffi::OsStrExt, ptr::null_mut };
-use crate::FileRef;
+use std::{ collections::HashMap, error::Error, sync::{ Arc, atomic::{ AtomicBool, Ordering } }, thread::{ self, JoinHandle }, time::SystemTime };
+#[cfg(windows)]
+use std::{ ffi::OsStr, iter::once, mem::zeroed, os::windows::ffi::OsStrExt, ptr::null_mut };
+#[cfg(not(windows))]
+use std::{ thread::sleep, time::Duration };
+use crate::{ FileRef, FileScanner, SEPARATOR };
+#[cfg(windows)]
 use winapi::{
 	um::{
-		winnt::{ FILE_LIST_DIRECTORY, FILE_SHARE_READ, FILE_SHARE_WRITE, FILE_SHARE_DELETE, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_CREATION, FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_NOTIFY_INFORMATION },
-		winbase::{ FILE_FLAG_BACKUP_SEMANTICS, ReadDirectoryChangesW },
-		handleapi::INVALID_HANDLE_VALUE,
-		fileapi::CreateFileW
+		winnt::{ HANDLE, FILE_LIST_DIRECTORY, FILE_SHARE_READ, FILE_SHARE_WRITE, FILE_SHARE_DELETE, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_CREATION, FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_NOTIFY_INFORMATION },
+		winbase::{ FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OVERLAPPED, INFINITE, WAIT_OBJECT_0, ReadDirectoryChangesW },
+		handleapi::{ CloseHandle, INVALID_HANDLE_VALUE },
+		fileapi::CreateFileW,
+		errhandlingapi::GetLastError,
+		ioapiset::{ CancelIo, GetOverlappedResult },
+		minwinbase::OVERLAPPED,
+		synchapi::{ CreateEventW, ResetEvent, SetEvent, WaitForMultipleObjects }
 	},
-	shared::minwindef::{ DWORD, TRUE, FALSE },
-	ctypes::c_void
+	shared::{ minwindef::{ DWORD, TRUE, FALSE }, winerror::ERROR_NOTIFY_ENUM_DIR }
 };
 
 
 
+#[cfg(not(windows))]
+const POLL_INTERVAL:Duration = Duration::from_millis(100);
+const DEFAULT_BUFFER_SIZE:usize = 64 * 1024;
+#[cfg(windows)]
+const MAX_BUFFER_SIZE:usize = 1024 * 1024;
+
+type FileHandler = Box<dyn Fn(&FileRef) -> Result<(), Box<dyn Error>> + Send>;
+type RenameHandler = Box<dyn Fn(&FileRef, &FileRef) -> Result<(), Box<dyn Error>> + Send>;
+pub type MonitorThread = JoinHandle<Result<(), String>>;
+type Snapshot = HashMap<FileRef, (SystemTime, u64)>;
+
+
+
 pub struct DirMonitor {
 	dir:FileRef,
 	recursive:bool,
+	buffer_size:usize,
 
-	on_add_file:Vec<Box<dyn Fn(&FileRef)>>,
-	on_remove_file:Vec<Box<dyn Fn(&FileRef)>>,
-	on_modify_file:Vec<Box<dyn Fn(&FileRef)>>,
-	on_rename_file:Vec<Box<dyn Fn(&FileRef, &FileRef)>>
+	on_add_file:Vec<FileHandler>,
+	on_remove_file:Vec<FileHandler>,
+	on_modify_file:Vec<FileHandler>,
+	on_rename_file:Vec<RenameHandler>
 }
 impl DirMonitor {
 
@@ -31,6 +53,7 @@ impl DirMonitor {
 		DirMonitor {
 			dir: FileRef::new(path),
 			recursive: false,
+			buffer_size: DEFAULT_BUFFER_SIZE,
 
 			on_add_file: Vec::new(),
 			on_remove_file: Vec::new(),
@@ -45,26 +68,52 @@ impl DirMonitor {
 		self
 	}
 
+	/// Return self with the size in bytes of the buffer receiving directory changes, 64 KiB by default. When a burst of changes does not fit the buffer, the changes are found by comparing the directory to its last known state instead and the buffer grows for the next changes. Only used on Windows, the polling monitor on other platforms does not buffer changes.
+	pub fn with_buffer_size(mut self, buffer_size:usize) -> Self {
+		self.buffer_size = buffer_size;
+		self
+	}
+
 	/// Return self with an 'on_add' event handler. Triggers the given function whenever a file is created with the new file as argument.
-	pub fn with_add_handler<T:Fn(&FileRef) + 'static>(mut self, handler:T) -> Self {
+	pub fn with_add_handler<T:Fn(&FileRef) + Send + 'static>(self, handler:T) -> Self {
+		self.try_with_add_handler(move |file| { handler(file); Ok(()) })
+	}
+
+	/// Return self with a fallible 'on_add' event handler. Triggers the given function whenever a file is created with the new file as argument. An error returned by the handler stops the monitor.
+	pub fn try_with_add_handler<T:Fn(&FileRef) -> Result<(), Box<dyn Error>> + Send + 'static>(mut self, handler:T) -> Self {
 		self.on_add_file.push(Box::new(handler));
 		self
 	}
 
 	/// Return self with an 'on_remove' event handler. Triggers the given function whenever a file is removed with the now nonexistent file as argument.
-	pub fn with_remove_handler<T:Fn(&FileRef) + 'static>(mut self, handler:T) -> Self {
+	pub fn with_remove_handler<T:Fn(&FileRef) + Send + 'static>(self, handler:T) -> Self {
+		self.try_with_remove_handler(move |file| { handler(file); Ok(()) })
+	}
+
+	/// Return self with a fallible 'on_remove' event handler. Triggers the given function whenever a file is removed with the now nonexistent file as argument. An error returned by the handler stops the monitor.
+	pub fn try_with_remove_handler<T:Fn(&FileRef) -> Result<(), Box<dyn Error>> + Send + 'static>(mut self, handler:T) -> Self {
 		self.on_remove_file.push(Box::new(handler));
 		self
 	}
 
 	/// Return self with an 'on_modify' event handler. Triggers the given function whenever a file is modified with the file as argument.
-	pub fn with_modify_handler<T:Fn(&FileRef) + 'static>(mut self, handler:T) -> Self {
+	pub fn with_modify_handler<T:Fn(&FileRef) + Send + 'static>(self, handler:T) -> Self {
+		self.try_with_modify_handler(move |file| { handler(file); Ok(()) })
+	}
+
+	/// Return self with a fallible 'on_modify' event handler. Triggers the given function whenever a file is modified with the file as argument. An error returned by the handler stops the monitor.
+	pub fn try_with_modify_handler<T:Fn(&FileRef) -> Result<(), Box<dyn Error>> + Send + 'static>(mut self, handler:T) -> Self {
 		self.on_modify_file.push(Box::new(handler));
 		self
 	}
 
 	/// Return self with an 'on_rename' event handler. Triggers the given function whenever a file is modified with the old filepath and new filepath as argument.
-	pub fn with_rename_handler<T:Fn(&FileRef, &FileRef) + 'static>(mut self, handler:T) -> Self {
+	pub fn with_rename_handler<T:Fn(&FileRef, &FileRef) + Send + 'static>(self, handler:T) -> Self {
+		self.try_with_rename_handler(move |origin, file| { handler(origin, file); Ok(()) })
+	}
+
+	/// Return self with a fallible 'on_rename' event handler. Triggers the given function whenever a file is modified with the old filepath and new filepath as argument. An error returned by the handler stops the monitor.
+	pub fn try_with_rename_handler<T:Fn(&FileRef, &FileRef) -> Result<(), Box<dyn Error>> + Send + 'static>(mut self, handler:T) -> Self {
 		self.on_rename_file.push(Box::new(handler));
 		self
 	}
@@ -73,13 +122,27 @@ impl DirMonitor {
 
 	/* USAGE METHODS */
 
-	/// Run forever, activating assigned handlers whenever an action is executed on the directory.
+	/// Run forever, activating assigned handlers whenever an action is executed on the directory. Returns the first error returned by a handler.
 	pub fn run(&self) -> Result<(), Box<dyn Error>> {
 		self.run_while(|_| true)
 	}
 
-	/// Run while the condition returns true. The condition gets the monitor's directory as argument and is only checked after a file modification. Keeps activating assigned handlers whenever an action is executed on the directory. 
+	/// Run while the condition returns true. The condition gets the monitor's directory as argument. On Windows it is only checked after a file modification, elsewhere the directory is polled and the condition is checked after every poll. Keeps activating assigned handlers whenever an action is executed on the directory. Returns the first error returned by a handler.
 	pub fn run_while<T:Fn(&FileRef) -> bool>(&self, condition:T) -> Result<(), Box<dyn Error>> {
+		self.run_until_stopped(condition, None)
+	}
+
+	/// Run on a separate thread until stopped through the returned handle, which stops the monitor promptly even when nothing happens in the directory. The thread returns the first error returned by a handler as a string.
+	pub fn run_handle(self) -> Result<(MonitorHandle, MonitorThread), Box<dyn Error>> {
+		let stop_handle:MonitorHandle = MonitorHandle::new()?;
+		let thread_stop_handle:MonitorHandle = stop_handle.clone();
+		let monitor_thread:MonitorThread = thread::spawn(move || self.run_until_stopped(|_| true, Some(&thread_stop_handle)).map_err(|error| error.to_string()));
+		Ok((stop_handle, monitor_thread))
+	}
+
+	/// Run until the condition returns false or the stop handle is stopped. The condition is only checked after a file modification, the stop handle is awaited alongside the directory changes.
+	#[cfg(windows)]
+	fn run_until_stopped<T:Fn(&FileRef) -> bool>(&self, condition:T, stop_handle:Option<&MonitorHandle>) -> Result<(), Box<dyn Error>> {
 
 		// Validate dir exists.
 		if !self.dir.exists() {
@@ -89,27 +152,77 @@ impl DirMonitor {
 
 		unsafe {
 
-			// Get a handle to the directory.
-			let target_dir_ptr:*mut winapi::ctypes::c_void = CreateFileW(path.as_ptr(), FILE_LIST_DIRECTORY, FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE, null_mut(), 3, FILE_FLAG_BACKUP_SEMANTICS, null_mut());
+			// Get an overlapped handle to the directory and an event signaling its changes.
+			let target_dir_ptr:HANDLE = CreateFileW(path.as_ptr(), FILE_LIST_DIRECTORY, FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE, null_mut(), 3, FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED, null_mut());
 			if target_dir_ptr == INVALID_HANDLE_VALUE {
 				return Err(format!("Failed to open directory '{}'.", self.dir).into());
 			}
+			let change_event:HANDLE = CreateEventW(null_mut(), TRUE, FALSE, null_mut());
+			if change_event.is_null() {
+				CloseHandle(target_dir_ptr);
+				return Err("Failed to create directory-change event.".into());
+			}
+
+			// Listen and release the handles whichever way listening ends.
+			let result:Result<(), Box<dyn Error>> = self.listen(target_dir_ptr, change_event, condition, stop_handle);
+			CloseHandle(change_event);
+			CloseHandle(target_dir_ptr);
+			result
+		}
+	}
+
+	/// Repeatedly listen for actions in the directory, executing handlers for each of them.
+	#[cfg(windows)]
+	fn listen<T:Fn(&FileRef) -> bool>(&self, target_dir_ptr:HANDLE, change_event:HANDLE, condition:T, stop_handle:Option<&MonitorHandle>) -> Result<(), Box<dyn Error>> {
+		let wait_handles:[HANDLE; 2] = [change_event, stop_handle.map(|stop_handle| stop_handle.stop_event.0).unwrap_or(null_mut())];
+		let wait_handles_count:DWORD = if stop_handle.is_some() { 2 } else { 1 };
+		let mut buffer:Vec<u32> = vec![0; self.buffer_size.div_ceil(4)]; // Notify information must be aligned to 4 bytes.
+		let mut snapshot:Snapshot = self.snapshot(&self.dir); // Last known state, to recover changes that did not fit the buffer.
+		while condition(&self.dir) && !stop_handle.is_some_and(|stop_handle| stop_handle.is_stopped()) {
+			unsafe {
 
-			// Repeatedly listen for actions in the directory.
-			let mut buffer:[u8; 1024] = [0u8; 1024];
-			while condition(&self.dir) {
+				// Start capturing a directory action.
+				let mut overlapped:OVERLAPPED = zeroed();
+				overlapped.hEvent = change_event;
+				ResetEvent(change_event);
+				if !self.read_dir_changes(target_dir_ptr, &mut buffer, &mut overlapped) {
+					return Err("Error reading directory-change message.".into());
+				}
 
-				// Try to capture a directory action.
+				// Wait for the action or a stop signal.
 				let mut bytes_returned:DWORD = 0;
-				if !self.read_dir_changes(target_dir_ptr, &mut buffer, &mut bytes_returned) {
+				match WaitForMultipleObjects(wait_handles_count, wait_handles.as_ptr(), FALSE, INFINITE) {
+					WAIT_OBJECT_0 => {},
+					signaled if signaled == WAIT_OBJECT_0 + 1 => {
+						Self::cancel_read(target_dir_ptr, &mut overlapped);
+						break;
+					},
+					_ => {
+						Self::cancel_read(target_dir_ptr, &mut overlapped);
+						return Err("Error awaiting directory-change message.".into());
+					}
+				}
+				let read_succeeded:bool = GetOverlappedResult(target_dir_ptr, &mut overlapped, &mut bytes_returned, FALSE) != 0;
+				if !read_succeeded && GetLastError() != ERROR_NOTIFY_ENUM_DIR {
 					return Err("Error reading directory-change message.".into());
 				}
 
+				// Too many changes to fit the buffer. Find them by comparing the directory to its last known state and grow the buffer for the next burst.
+				if !read_succeeded || bytes_returned == 0 {
+					if buffer.len() * 4 < MAX_BUFFER_SIZE {
+						buffer = vec![0; buffer.len() * 2];
+					}
+					let new_snapshot:Snapshot = self.snapshot(&self.dir);
+					self.handle_snapshot_changes(&snapshot, &new_snapshot)?;
+					snapshot = new_snapshot;
+					continue;
+				}
+
 				// Iterate through file-notify-information in the action.
 				let mut offset:usize = 0;
 				let mut file_moving_origin:FileRef = FileRef::new("");
 				loop {
-					let fni:&FILE_NOTIFY_INFORMATION = &*(buffer.as_ptr().add(offset as usize) as *const FILE_NOTIFY_INFORMATION);
+					let fni:&FILE_NOTIFY_INFORMATION = &*((buffer.as_ptr() as *const u8).add(offset) as *const FILE_NOTIFY_INFORMATION);
 
 					// Build file path from file-notify-information.
 					let filename_len:usize = (fni.FileNameLength / 2) as usize;
@@ -117,13 +230,26 @@ impl DirMonitor {
 					let filename:String = String::from_utf16_lossy(&filename);
 					let file:FileRef = self.dir.clone() + "/" + &filename;
 
-					// Execute handlers according to action type.
+					// Execute handlers according to action type, stopping the monitor on the first error.
 					match fni.Action {
-						1 => self.on_add_file.iter().for_each(|handler| handler(&file)),
-						2 => self.on_remove_file.iter().for_each(|handler| handler(&file)),
-						3 => self.on_modify_file.iter().for_each(|handler| handler(&file)),
+						1 => {
+							self.remember(&mut snapshot, &file);
+							self.on_add_file.iter().try_for_each(|handler| handler(&file))?
+						},
+						2 => {
+							Self::forget(&mut snapshot, &file);
+							self.on_remove_file.iter().try_for_each(|handler| handler(&file))?
+						},
+						3 => {
+							self.remember(&mut snapshot, &file);
+							self.on_modify_file.iter().try_for_each(|handler| handler(&file))?
+						},
 						4 => file_moving_origin = file,
-						5 => self.on_rename_file.iter().for_each(|handler| handler(&file_moving_origin, &file)),
+						5 => {
+							Self::forget(&mut snapshot, &file_moving_origin);
+							self.remember(&mut snapshot, &file);
+							self.on_rename_file.iter().try_for_each(|handler| handler(&file_moving_origin, &file))?
+						},
 						_ => {},
 					}
 
@@ -140,19 +266,174 @@ impl DirMonitor {
 		Ok(())
 	}
 
-	/// Read directory changes once. Keeps the thread until a change is made. Returns false if something went wrong.
-	fn read_dir_changes(&self, target_dir_ptr:*mut c_void, buffer:&mut [u8; 1024], bytes_returned:&mut DWORD) -> bool {
+	/// Run until the condition returns false or the stop handle is stopped, both are checked after every poll. Periodically scans the directory and activates assigned handlers for files that were added, removed or modified since the previous scan. Renames show up as a removal followed by an addition, as polling can not relate the two.
+	#[cfg(not(windows))]
+	fn run_until_stopped<T:Fn(&FileRef) -> bool>(&self, condition:T, stop_handle:Option<&MonitorHandle>) -> Result<(), Box<dyn Error>> {
+
+		// Validate dir exists.
+		if !self.dir.exists() {
+			return Err(format!("Cannot monitor dir '{}' as it does not exist.", self.dir).into());
+		}
+
+		// Repeatedly compare the directory to the previous snapshot.
+		let mut snapshot:Snapshot = self.snapshot(&self.dir);
+		while condition(&self.dir) && !stop_handle.is_some_and(|stop_handle| stop_handle.is_stopped()) {
+			sleep(POLL_INTERVAL);
+			let new_snapshot:Snapshot = self.snapshot(&self.dir);
+			self.handle_snapshot_changes(&snapshot, &new_snapshot)?;
+			snapshot = new_snapshot;
+		}
+
+		// Return success.
+		Ok(())
+	}
+
+	/// Activate assigned handlers for files that were added, removed or modified between two snapshots. Stops on the first error returned by a handler.
+	fn handle_snapshot_changes(&self, snapshot:&Snapshot, new_snapshot:&Snapshot) -> Result<(), Box<dyn Error>> {
+
+		// Find changes, sorted by path to keep the order of handler calls predictable.
+		let mut added:Vec<&FileRef> = new_snapshot.keys().filter(|file| !snapshot.contains_key(file)).collect();
+		let mut removed:Vec<&FileRef> = snapshot.keys().filter(|file| !new_snapshot.contains_key(file)).collect();
+		let mut modified:Vec<&FileRef> = new_snapshot.iter().filter(|(file, state)| snapshot.get(file).is_some_and(|old_state| old_state != *state)).map(|(file, _)| file).collect();
+		for files in [&mut added, &mut removed, &mut modified] {
+			files.sort_by(|a, b| a.path().cmp(b.path()));
+		}
+
+		// Execute handlers.
+		for file in added {
+			self.on_add_file.iter().try_for_each(|handler| handler(file))?;
+		}
+		for file in modified {
+			self.on_modify_file.iter().try_for_each(|handler| handler(file))?;
+		}
+		for file in removed {
+			self.on_remove_file.iter().try_for_each(|handler| handler(file))?;
+		}
+		Ok(())
+	}
+
+	/// Get the modification time and size of all files in the given dir, keyed by their path in the same form as the given dir. Includes sub-dirs when monitoring recursively.
+	fn snapshot(&self, dir:&FileRef) -> Snapshot {
+		let root_dir:FileRef = dir.clone().absolute().trim_end_matches(SEPARATOR);
+		let mut scanner:FileScanner = FileScanner::new(&root_dir).include_files();
+		if self.recursive {
+			scanner = scanner.recurse();
+		}
+		scanner
+			.filter_map(|file| {
+				let modified:SystemTime = file.get_time_modification().ok()?;
+				let monitored_file:FileRef = dir.clone() + SEPARATOR + &file.path()[root_dir.len() + 1..];
+				Some((monitored_file, (modified, file.bytes_size())))
+			})
+			.collect()
+	}
+
+	/// Store the current state of a changed file in the snapshot. For a dir, the files in it are stored instead.
+	#[cfg(windows)]
+	fn remember(&self, snapshot:&mut Snapshot, file:&FileRef) {
+		if !file.exists() {
+			return;
+		}
+		if file.is_dir() {
+			snapshot.extend(self.snapshot(file));
+		} else if let Ok(modified) = file.get_time_modification() {
+			snapshot.insert(file.clone(), (modified, file.bytes_size()));
+		}
+	}
+
+	/// Remove a file, or a dir and all files in it, from the snapshot.
+	#[cfg(windows)]
+	fn forget(snapshot:&mut Snapshot, file:&FileRef) {
+		let dir_prefix:String = file.path().to_owned() + SEPARATOR;
+		snapshot.retain(|snapshot_file, _| snapshot_file != file && !snapshot_file.path().starts_with(&dir_prefix));
+	}
+
+	/// Cancel a pending directory read and wait until it is no longer using its buffer and overlapped structure, so both can safely be released.
+	#[cfg(windows)]
+	unsafe fn cancel_read(target_dir_ptr:HANDLE, overlapped:&mut OVERLAPPED) {
+		let mut bytes_returned:DWORD = 0;
+		CancelIo(target_dir_ptr);
+		GetOverlappedResult(target_dir_ptr, overlapped, &mut bytes_returned, TRUE);
+	}
+
+	/// Start reading directory changes once. Returns immediately, the overlapped event is signaled once a change is made. Returns false if something went wrong.
+	#[cfg(windows)]
+	fn read_dir_changes(&self, target_dir_ptr:HANDLE, buffer:&mut [u32], overlapped:&mut OVERLAPPED) -> bool {
 		unsafe {
 			ReadDirectoryChangesW(
 				target_dir_ptr,
-				(*buffer).as_mut_ptr() as *mut _,
-				buffer.len() as DWORD,
+				buffer.as_mut_ptr() as *mut _,
+				(buffer.len() * 4) as DWORD,
 				if self.recursive { TRUE } else { FALSE },
 				FILE_NOTIFY_CHANGE_FILE_NAME | FILE_NOTIFY_CHANGE_CREATION | FILE_NOTIFY_CHANGE_LAST_WRITE,
-				bytes_returned,
 				null_mut(),
+				overlapped,
 				None
 			) != 0
 		}
 	}
+}
+
+
+
+#[derive(Clone)]
+pub struct MonitorHandle {
+	stopped:Arc<AtomicBool>,
+	#[cfg(windows)]
+	stop_event:Arc<StopEvent>
+}
+impl MonitorHandle {
+
+	/// Create a new handle that has not been stopped yet.
+	fn new() -> Result<MonitorHandle, Box<dyn Error>> {
+		Ok(MonitorHandle {
+			stopped: Arc::new(AtomicBool::new(false)),
+			#[cfg(windows)]
+			stop_event: Arc::new(StopEvent::new()?)
+		})
+	}
+
+	/// Signal the monitor to stop.
+	pub fn stop(&self) {
+		self.stopped.store(true, Ordering::SeqCst);
+		#[cfg(windows)]
+		unsafe {
+			SetEvent(self.stop_event.0);
+		}
+	}
+
+	/// Check if the monitor was signaled to stop.
+	pub fn is_stopped(&self) -> bool {
+		self.stopped.load(Ordering::SeqCst)
+	}
+}
+
+
+
+#[cfg(windows)]
+struct StopEvent(HANDLE);
+#[cfg(windows)]
+impl StopEvent {
+
+	/// Create a new manual-reset event that is not signaled yet.
+	fn new() -> Result<StopEvent, Box<dyn Error>> {
+		let event:HANDLE = unsafe { CreateEventW(null_mut(), TRUE, FALSE, null_mut()) };
+		if event.is_null() {
+			Err("Failed to create monitor stop event.".into())
+		} else {
+			Ok(StopEvent(event))
+		}
+	}
+}
+#[cfg(windows)]
+unsafe impl Send for StopEvent {} // Event handles can be used from any thread.
+#[cfg(windows)]
+unsafe impl Sync for StopEvent {}
+#[cfg(windows)]
+impl Drop for StopEvent {
+	fn drop(&mut self) {
+		unsafe {
+			CloseHandle(self.0);
+		}
+	}
 }
\ No newline at end of file
@@ -1,11 +1,12 @@
 #[cfg(test)]
 mod tests {
-	use std::{ sync::Mutex, thread::{ self, sleep }, time::Duration };
-	use crate::{ DirMonitor, FileRef };
+	use std::{ sync::Mutex, thread::{ self, sleep, JoinHandle }, time::{ Duration, Instant } };
+	use crate::{ DirMonitor, FileRef, MonitorHandle, MonitorThread };
 
 
 
 	#[test]
+	#[cfg(windows)]
 	fn dir_monitor_full_test() {
 
 		// Prepare temp dir.
@@ -64,4 +65,192 @@ mod tests {
 			temp_dir.delete().unwrap();
 		}
 	}
+
+	#[test]
+	fn dir_monitor_handler_error_test() {
+
+		// Prepare temp dir.
+		let temp_dir:FileRef = FileRef::new("target/dir_monitor_error_test");
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+		temp_dir.create().unwrap();
+
+		// Create monitor with a failing handler and run in separate thread.
+		let temp_dir_clone:FileRef = temp_dir.clone();
+		let monitor_thread:JoinHandle<Result<(), String>> = thread::spawn(move || {
+			let monitor:DirMonitor = DirMonitor::new(temp_dir_clone.path())
+							.try_with_add_handler(|file| Err(format!("handler failed on {}", file.name()).into()));
+			monitor.run_while(|_| true).map_err(|error| error.to_string())
+		});
+
+		// Trigger the handler and validate the monitor stopped with its error.
+		sleep(Duration::from_millis(250));
+		(temp_dir.clone() + "/file_a.txt").create().unwrap();
+		assert_eq!(monitor_thread.join().unwrap(), Err("handler failed on file_a.txt".to_string()));
+
+		// Delete temp dir.
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+	}
+
+	#[test]
+	#[cfg(not(windows))]
+	fn dir_monitor_polling_test() {
+
+		// Prepare temp dir.
+		let temp_dir:FileRef = FileRef::new("target/dir_monitor_polling_test");
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+		temp_dir.create().unwrap();
+		(temp_dir.clone() + "/existing.txt").write("T".to_string()).unwrap();
+
+		// Create monitor and run in separate thread.
+		static MONITOR_ACTIVE:Mutex<bool> = Mutex::new(true);
+		static HISTORY:Mutex<Vec<String>> = Mutex::new(Vec::new());
+		let temp_dir_clone:FileRef = temp_dir.clone();
+		let monitor_thread:JoinHandle<()> = thread::spawn(move || {
+			let monitor:DirMonitor = DirMonitor::new(temp_dir_clone.path())
+							.recursive()
+							.with_add_handler(|file| HISTORY.lock().unwrap().push(format!("add {}", file.clone())))
+							.with_remove_handler(|file| HISTORY.lock().unwrap().push(format!("remove {}", file.clone())))
+							.with_modify_handler(|file| HISTORY.lock().unwrap().push(format!("modify {}", file.clone())));
+			monitor.run_while(|_| *MONITOR_ACTIVE.lock().unwrap()).unwrap();
+		});
+
+		// Trigger actions in dir, giving the monitor time to poll in between.
+		let actions:Vec<Box<dyn Fn()>> = vec![
+			Box::new(|| (temp_dir.clone() + "/file_a.txt").write("T".to_string()).unwrap()),
+			Box::new(|| (temp_dir.clone() + "/existing.txt").write("TT".to_string()).unwrap()),
+			Box::new(|| (temp_dir.clone() + "/subdir/file_b.txt").write("T".to_string()).unwrap()),
+			Box::new(|| (temp_dir.clone() + "/file_a.txt").delete().unwrap())
+		];
+		for action in actions {
+			sleep(Duration::from_millis(250));
+			action();
+		}
+		sleep(Duration::from_millis(250));
+
+		// Quit monitor.
+		*MONITOR_ACTIVE.lock().unwrap() = false;
+		monitor_thread.join().unwrap();
+
+		// Validate correct history.
+		const EXPECTED_HISTORY:&[&str] = &[
+			"add target/dir_monitor_polling_test/file_a.txt",
+			"modify target/dir_monitor_polling_test/existing.txt",
+			"add target/dir_monitor_polling_test/subdir/file_b.txt",
+			"remove target/dir_monitor_polling_test/file_a.txt"
+		];
+		assert_eq!(*HISTORY.lock().unwrap(), EXPECTED_HISTORY);
+
+		// Delete temp dir.
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+	}
+
+	#[test]
+	fn dir_monitor_handle_test() {
+
+		// Prepare temp dir.
+		let temp_dir:FileRef = FileRef::new("target/dir_monitor_handle_test");
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+		temp_dir.create().unwrap();
+
+		// Run monitor on a quiet dir and stop it through its handle.
+		static HISTORY:Mutex<Vec<String>> = Mutex::new(Vec::new());
+		let monitor:DirMonitor = DirMonitor::new(temp_dir.path()).with_add_handler(|file| HISTORY.lock().unwrap().push(format!("add {}", file.name())));
+		let (stop_handle, monitor_thread):(MonitorHandle, MonitorThread) = monitor.run_handle().unwrap();
+		sleep(Duration::from_millis(250));
+		(temp_dir.clone() + "/file_a.txt").create().unwrap();
+		sleep(Duration::from_millis(250));
+		assert!(!stop_handle.is_stopped());
+
+		let stop_time:Instant = Instant::now();
+		stop_handle.stop();
+		assert!(stop_handle.is_stopped());
+		assert_eq!(monitor_thread.join().unwrap(), Ok(()));
+		assert!(stop_time.elapsed() < Duration::from_millis(500));
+		assert_eq!(*HISTORY.lock().unwrap(), vec!["add file_a.txt".to_string()]);
+
+		// Delete temp dir.
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+	}
+
+	#[test]
+	fn dir_monitor_burst_test() {
+
+		// Prepare temp dir.
+		let temp_dir:FileRef = FileRef::new("target/dir_monitor_burst_test");
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+		temp_dir.create().unwrap();
+
+		// Create a burst of files at once and validate none were missed.
+		static ADDED_COUNT:Mutex<usize> = Mutex::new(0);
+		let monitor:DirMonitor = DirMonitor::new(temp_dir.path()).with_buffer_size(16 * 1024).with_add_handler(|_| *ADDED_COUNT.lock().unwrap() += 1);
+		let (stop_handle, monitor_thread):(MonitorHandle, MonitorThread) = monitor.run_handle().unwrap();
+		sleep(Duration::from_millis(250));
+		for index in 0..200 {
+			(temp_dir.clone() + &format!("/burst_file_{index}.txt")).create().unwrap();
+		}
+		sleep(Duration::from_millis(500));
+		stop_handle.stop();
+		monitor_thread.join().unwrap().unwrap();
+		assert_eq!(*ADDED_COUNT.lock().unwrap(), 200);
+
+		// Delete temp dir.
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+	}
+
+	#[test]
+	#[cfg(windows)]
+	fn dir_monitor_overflow_test() {
+		use std::collections::HashSet;
+
+		// Prepare temp dir.
+		let temp_dir:FileRef = FileRef::new("target/dir_monitor_overflow_test");
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+		temp_dir.create().unwrap();
+		(temp_dir.clone() + "/existing.txt").write("T".to_string()).unwrap();
+
+		// Use a buffer too small for any change, so every burst overflows it until it has grown enough.
+		static ADDED:Mutex<Option<HashSet<String>>> = Mutex::new(None);
+		static REMOVED:Mutex<Option<HashSet<String>>> = Mutex::new(None);
+		let monitor:DirMonitor = DirMonitor::new(temp_dir.path())
+			.with_buffer_size(4)
+			.with_add_handler(|file| { ADDED.lock().unwrap().get_or_insert_with(HashSet::new).insert(file.name().to_string()); })
+			.with_remove_handler(|file| { REMOVED.lock().unwrap().get_or_insert_with(HashSet::new).insert(file.name().to_string()); });
+		let (stop_handle, monitor_thread):(MonitorHandle, MonitorThread) = monitor.run_handle().unwrap();
+		sleep(Duration::from_millis(250));
+		for index in 0..200 {
+			(temp_dir.clone() + &format!("/burst_file_{index}.txt")).create().unwrap();
+		}
+		(temp_dir.clone() + "/existing.txt").delete().unwrap();
+		sleep(Duration::from_millis(500));
+		stop_handle.stop();
+		monitor_thread.join().unwrap().unwrap();
+
+		// Validate no changes were lost.
+		let added:HashSet<String> = ADDED.lock().unwrap().take().unwrap_or_default();
+		assert_eq!(added, (0..200).map(|index| format!("burst_file_{index}.txt")).collect::<HashSet<String>>());
+		assert_eq!(REMOVED.lock().unwrap().take().unwrap_or_default(), HashSet::from(["existing.txt".to_string()]));
+
+		// Delete temp dir.
+		if temp_dir.exists() {
+			temp_dir.delete().unwrap();
+		}
+	}
 }
\ No newline at end of file
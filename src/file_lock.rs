@@ -0,0 +1,62 @@
+use std::{ error::Error, fs::{ File, OpenOptions } };
+use crate::FileRef;
+
+
+
+pub struct FileLock {
+	file:FileRef,
+	handle:File
+}
+impl FileLock {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Lock the given file exclusively, waiting until no other process holds a lock on it. Creates the file if it does not exist yet. The lock is advisory, so it only holds off other processes that lock the file as well.
+	pub fn exclusive(file:&FileRef) -> Result<FileLock, Box<dyn Error>> {
+		let lock:FileLock = FileLock::open(file)?;
+		lock.handle.lock()?;
+		Ok(lock)
+	}
+
+	/// Lock the given file shared, waiting until no other process holds an exclusive lock on it. Any number of shared locks can be held at once. Creates the file if it does not exist yet.
+	pub fn shared(file:&FileRef) -> Result<FileLock, Box<dyn Error>> {
+		let lock:FileLock = FileLock::open(file)?;
+		lock.handle.lock_shared()?;
+		Ok(lock)
+	}
+
+	/// Open a handle on the file to lock.
+	fn open(file:&FileRef) -> Result<FileLock, Box<dyn Error>> {
+		if file.is_dir() {
+			return Err(format!("Could not lock dir \"{}\". Only able to lock files.", file.path()).into());
+		}
+		file.guarantee_parent_dir()?;
+		Ok(FileLock {
+			file: file.clone(),
+			handle: OpenOptions::new().read(true).write(true).create(true).truncate(false).open(file.path())?
+		})
+	}
+
+
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the locked file.
+	pub fn file(&self) -> &FileRef {
+		&self.file
+	}
+
+
+
+	/* USAGE METHODS */
+
+	/// Release the lock. Dropping the lock does the same.
+	pub fn unlock(self) -> Result<(), Box<dyn Error>> {
+		self.handle.unlock().map_err(|error| error.into())
+	}
+}
+impl Drop for FileLock {
+	fn drop(&mut self) {
+		let _ = self.handle.unlock();
+	}
+}
\ No newline at end of file
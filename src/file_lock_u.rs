@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+	use std::fs::File;
+	use crate::{ FileLock, FileRef, unit_test_support::TempFile };
+
+
+
+	#[test]
+	fn test_lock_exclusive() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/state.json";
+
+		let lock:FileLock = temp_file_ref.lock_exclusive().unwrap();
+		assert!(temp_file_ref.exists());
+		let other_handle:File = File::open(temp_file_ref.path()).unwrap();
+		assert!(other_handle.try_lock_shared().is_err());
+
+		drop(lock);
+		assert!(other_handle.try_lock_shared().is_ok());
+	}
+
+	#[test]
+	fn test_lock_shared() {
+		let temp_file:TempFile = TempFile::new(Some("json"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+
+		let lock:FileLock = temp_file_ref.lock_shared().unwrap();
+		let other_lock:FileLock = temp_file_ref.lock_shared().unwrap();
+		let other_handle:File = File::open(temp_file_ref.path()).unwrap();
+		assert!(other_handle.try_lock().is_err());
+
+		lock.unlock().unwrap();
+		other_lock.unlock().unwrap();
+		assert!(other_handle.try_lock().is_ok());
+		assert!(temp_file_ref.parent_dir().unwrap().lock_shared().is_err());
+	}
+}
\ No newline at end of file
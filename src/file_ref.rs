@@ -1,6 +1,6 @@
-use std::{ error::Error, time::SystemTime, fs::{ Metadata, Permissions }, ops::{ Add, AddAssign } };
+use std::{ borrow::Cow, cmp::Ordering, convert::Infallible, error::Error, time::{ Duration, SystemTime }, fs::{ FileTimes, Metadata, Permissions }, hash::{ Hash, Hasher }, io::{ Read, Seek }, ops::{ Add, AddAssign }, path::{ Path, PathBuf }, str::FromStr };
 use core::fmt::{ self, Display, Debug, Formatter };
-use crate::FileScanner;
+use crate::{ FileLock, FileScanner, FileTransaction };
 
 
 
@@ -9,12 +9,15 @@ pub(crate) const SEPARATOR:&str = "/";
 const INVALID_SEPARATOR:&str = "\\";
 const DOUBLE_SEPARATOR:&str = "//";
 const DISK_SEPARATOR:&str = ":";
+const DEFAULT_TIMESTAMP_FORMAT:&str = "%Y-%m-%dT%H-%M-%S";
+const COPY_CHUNK_SIZE:usize = 64 * 1024;
 
 
 
 #[derive(Clone, Eq, PartialOrd, Ord)]
 pub(crate) enum FilePath {
 	StaticStr(&'static str),
+	Embedded(&'static str, &'static str),
 	Owned(String)
 }
 impl FilePath {
@@ -58,10 +61,15 @@ impl FilePath {
 		FilePath::StaticStr(path)
 	}
 
+	/// Create a new statically borrowed path with its contents embedded at compile time.
+	pub const fn new_embedded(path:&'static str, contents:&'static str) -> FilePath {
+		FilePath::Embedded(path, contents)
+	}
+
 	/// Get the raw path.
 	pub fn path(&self) -> &str {
 		match self {
-			FilePath::StaticStr(path) => *path,
+			FilePath::StaticStr(path) | FilePath::Embedded(path, _) => *path,
 			FilePath::Owned(path) => path.as_str()
 		}
 	}
@@ -74,20 +82,91 @@ impl PartialEq<FilePath> for FilePath {
 
 
 
-#[derive(Clone, Eq, PartialOrd, Ord)]
-pub struct FileRef(FilePath);
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineEnding {
+	Lf,
+	CrLf
+}
+impl LineEnding {
+
+	/// Get the characters that make up the line ending.
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			LineEnding::Lf => "\n",
+			LineEnding::CrLf => "\r\n"
+		}
+	}
+
+	/// Convert all line endings ('\r\n', '\r' and '\n') in the given text to '\n'.
+	pub(crate) fn normalize(text:&str) -> String {
+		text.replace("\r\n", "\n").replace('\r', "\n")
+	}
+}
+
+
+
+#[cfg(feature="encoding")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Encoding {
+	Utf8,
+	Utf16Le,
+	Utf16Be,
+	Latin1
+}
+
+
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConflictPolicy {
+	Skip,
+	Overwrite,
+	Rename
+}
+
+
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PathKind {
+	File,
+	Dir
+}
+
+
+
+pub type FileErrors = Vec<(FileRef, Box<dyn Error>)>;
+#[derive(Clone, Eq)]
+pub struct FileRef(FilePath, Option<PathKind>);
 impl FileRef {
 
 	/* CONSTRUCTOR METHODS */
 
 	/// Create a new owned path.
 	pub fn new(path:&str) -> FileRef {
-		FileRef(FilePath::new(path))
+		FileRef(FilePath::new(path), None)
+	}
+
+	/// Create a new owned path, erroring if it contains characters the platform does not allow in paths. NUL bytes are rejected everywhere, on Windows control characters and `<>:"|?*` are rejected as well, except for the colon of a drive letter.
+	pub fn new_validated(path:&str) -> Result<FileRef, Box<dyn Error>> {
+		if path.contains('\0') {
+			return Err(format!("Invalid path \"{}\". Paths can not contain NUL bytes.", path.escape_debug()).into());
+		}
+		if cfg!(windows) {
+			let drive_prefix_len:usize = if path.len() >= 2 && path.as_bytes()[0].is_ascii_alphabetic() && path.as_bytes()[1] == b':' { 2 } else { 0 };
+			if let Some(character) = path[drive_prefix_len..].chars().find(|character| character.is_ascii_control() || "<>:\"|?*".contains(*character)) {
+				return Err(format!("Invalid path \"{}\". Paths can not contain '{}' on Windows.", path.escape_debug(), character.escape_debug()).into());
+			}
+		}
+		Ok(FileRef::new(path))
 	}
 
 	/// Create a new statically borrowed path. This may behave unexpectedly for messy paths (using '.' or '..').
 	pub const fn new_const(path:&'static str) -> FileRef {
-		FileRef(FilePath::new_const(path))
+		FileRef(FilePath::new_const(path), None)
+	}
+
+	/// Create a new statically borrowed path with contents embedded at compile time, like `FileRef::new_embedded("config/defaults.toml", include_str!("../config/defaults.toml"))`. Reading it through `read_cow` returns the embedded contents without touching the disk.
+	pub const fn new_embedded(path:&'static str, contents:&'static str) -> FileRef {
+		FileRef(FilePath::new_embedded(path, contents), None)
 	}
 
 	/// Get the working dir of the application.
@@ -100,7 +179,16 @@ impl FileRef {
 		if self.is_absolute_path() {
 			self
 		} else {
-			FileRef::working_dir() + "/" + self.path()
+			self.absolute_from(&FileRef::working_dir())
+		}
+	}
+
+	/// Return self with a absolute path, using the given dir instead of the working dir as base for relative paths.
+	pub(crate) fn absolute_from(self, base_dir:&FileRef) -> FileRef {
+		if self.is_absolute_path() {
+			self
+		} else {
+			base_dir.clone() + "/" + self.path()
 		}
 	}
 
@@ -114,12 +202,21 @@ impl FileRef {
 		}
 	}
 
-	/// Create a relative path from self to another path.
+	/// Create a relative path from self to another path. Relative paths are made absolute using the working dir first, so mixing relative and absolute paths gives a path through the working dir. Trailing separators are ignored, so a dir target results in the same path with or without one.
 	pub fn relative_path_to(&self, target:&FileRef) -> FileRef {
+		Self::relative_path_between(self.clone().absolute(), target.clone().absolute())
+	}
+
+	/// Create a relative path from self to another path, making relative paths absolute using the given base dir instead of the working dir. This makes the result independent of the working dir of the process.
+	pub fn relative_path_to_from(&self, target:&FileRef, base_dir:&FileRef) -> FileRef {
+		let base_dir:FileRef = base_dir.clone().absolute();
+		Self::relative_path_between(self.clone().absolute_from(&base_dir), target.clone().absolute_from(&base_dir))
+	}
+
+	/// Create a relative path between two absolute paths.
+	fn relative_path_between(source_path:FileRef, target_path:FileRef) -> FileRef {
 
 		// Process both paths as equal as possible.
-		let source_path:FileRef = self.clone().absolute();
-		let target_path:FileRef = target.clone().absolute();
 		let mut source_steps:Vec<&str> = source_path.path_nodes();
 		let mut target_steps:Vec<&str> = target_path.path_nodes();
 
@@ -135,6 +232,84 @@ impl FileRef {
 	}
 
 
+	/// Create a path to another file or dir in the same dir. Errors if the name contains a path separator.
+	pub fn sibling(&self, name:&str) -> Result<FileRef, Box<dyn Error>> {
+		if name.contains(SEPARATOR) || name.contains(INVALID_SEPARATOR) {
+			Err(format!("Could not get sibling \"{name}\" of \"{}\". The name can not contain a path separator.", self.path()).into())
+		} else {
+			Ok(self.parent_dir()? + &(SEPARATOR.to_owned() + name))
+		}
+	}
+
+	/// Create a path with the extension replaced by the given one, or added if there is none. Only the final extension is replaced. An empty extension strips the extension.
+	pub fn with_extension(&self, extension:&str) -> FileRef {
+		let name:&str = self.name();
+		let stem:&str = match self.extension() {
+			Some(current_extension) => &name[..name.len() - current_extension.len() - 1],
+			None => name
+		};
+		if extension.is_empty() {
+			self.with_name(stem)
+		} else {
+			self.with_name(&format!("{stem}.{extension}"))
+		}
+	}
+
+	/// Create a sibling path with a formatted timestamp inserted between the file name and extension, like 'report-2024-06-01T12-30-00.txt'. The format supports %Y, %m, %d, %H, %M and %S in UTC, an empty format uses '%Y-%m-%dT%H-%M-%S'.
+	pub fn with_timestamp(&self, time:SystemTime, format:&str) -> FileRef {
+		let format:&str = if format.is_empty() { DEFAULT_TIMESTAMP_FORMAT } else { format };
+		let timestamp:String = Self::format_timestamp(time, format);
+		let name:&str = self.name();
+		match self.extension() {
+			Some(extension) => self.with_name(&format!("{}-{timestamp}.{extension}", &name[..name.len() - extension.len() - 1])),
+			None => self.with_name(&format!("{name}-{timestamp}"))
+		}
+	}
+
+	/// Format a time in UTC using %Y, %m, %d, %H, %M and %S placeholders. Times before the unix epoch are formatted as the epoch.
+	fn format_timestamp(time:SystemTime, format:&str) -> String {
+		let seconds:u64 = time.duration_since(SystemTime::UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+		let (days, seconds_of_day):(u64, u64) = (seconds / 86400, seconds % 86400);
+
+		// Convert days since epoch to a civil date.
+		let shifted_days:u64 = days + 719468;
+		let era:u64 = shifted_days / 146097;
+		let day_of_era:u64 = shifted_days % 146097;
+		let year_of_era:u64 = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+		let day_of_year:u64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+		let shifted_month:u64 = (5 * day_of_year + 2) / 153;
+		let day:u64 = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+		let month:u64 = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+		let year:u64 = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+		format
+			.replace("%Y", &format!("{year:04}"))
+			.replace("%m", &format!("{month:02}"))
+			.replace("%d", &format!("{day:02}"))
+			.replace("%H", &format!("{:02}", seconds_of_day / 3600))
+			.replace("%M", &format!("{:02}", seconds_of_day % 3600 / 60))
+			.replace("%S", &format!("{:02}", seconds_of_day % 60))
+	}
+
+	/// Create a path in the same dir with the given name.
+	fn with_name(&self, name:&str) -> FileRef {
+		let path:&str = self.path();
+		FileRef::new(&(path[..path.len() - self.name().len()].to_owned() + name))
+	}
+
+	/// Return self marked as a file, so it is treated as a file while it does not exist, regardless of its extension. Paths derived from self do not keep the mark.
+	pub fn as_file(mut self) -> Self {
+		self.1 = Some(PathKind::File);
+		self
+	}
+
+	/// Return self marked as a dir, so it is treated as a dir while it does not exist, regardless of its extension. Paths derived from self do not keep the mark.
+	pub fn as_dir(mut self) -> Self {
+		self.1 = Some(PathKind::Dir);
+		self
+	}
+
+
 
 	/* PROPERTY GETTER METHODS */
 
@@ -143,6 +318,24 @@ impl FileRef {
 		self.0.path()
 	}
 
+	/// Get the path with the separators of the current platform, '\\' on Windows and '/' elsewhere. Does not change self.
+	pub fn to_native_separators(&self) -> String {
+		self.path().replace(SEPARATOR, std::path::MAIN_SEPARATOR_STR)
+	}
+
+	/// Get the path as an owned std `PathBuf`. Separators stay '/', which Windows accepts as well.
+	pub fn to_path_buf(&self) -> PathBuf {
+		PathBuf::from(self.path())
+	}
+
+	/// Convert self into an owned std `PathBuf`. Separators stay '/', which Windows accepts as well.
+	pub fn into_path_buf(self) -> PathBuf {
+		match self.0 {
+			FilePath::StaticStr(path) | FilePath::Embedded(path, _) => PathBuf::from(path),
+			FilePath::Owned(path) => PathBuf::from(path)
+		}
+	}
+
 	/// Get the directory the file is in.
 	pub fn parent_dir(&self) -> Result<FileRef, Box<dyn Error>> {
 		let path:&str = self.path();
@@ -161,6 +354,73 @@ impl FileRef {
 		}
 	}
 
+	/// Get the path with the casing of each node as it is stored on disk. On case-insensitive file systems the casing of the path might differ from the actual files. Errors if the path does not exist.
+	pub fn real_case(&self) -> Result<FileRef, Box<dyn Error>> {
+		let nodes:Vec<&str> = self.path_nodes();
+		let (mut disk_dir, mut real_nodes, remaining_nodes):(FileRef, Vec<String>, &[&str]) = if self.is_absolute_path() && !nodes.is_empty() {
+			(FileRef::new(&(nodes[0].to_owned() + SEPARATOR)), vec![nodes[0].to_owned()], &nodes[1..])
+		} else {
+			(FileRef::working_dir(), Vec::new(), &nodes[..])
+		};
+		for node in remaining_nodes {
+			let real_node:String = if *node == "." || *node == ".." {
+				node.to_string()
+			} else {
+				let entry_names:Vec<String> = std::fs::read_dir(disk_dir.path())?.flatten().map(|entry| entry.file_name().to_string_lossy().to_string()).collect();
+				match entry_names.iter().find(|name| name == node).or_else(|| entry_names.iter().find(|name| name.to_lowercase() == node.to_lowercase())) {
+					Some(name) => name.to_string(),
+					None => return Err(format!("Could not get real casing of \"{}\". Path does not exist.", self.path()).into())
+				}
+			};
+			disk_dir = disk_dir + SEPARATOR + &real_node;
+			real_nodes.push(real_node);
+		}
+		Ok(FileRef::new(&real_nodes.join(SEPARATOR)))
+	}
+
+	/// Get the real absolute path, with symlinks and '..' resolved against the file system. On Windows the verbatim `\\?\` prefix is removed. Errors if the path does not exist.
+	pub fn canonicalize(&self) -> Result<FileRef, Box<dyn Error>> {
+		let canonical_path:PathBuf = std::fs::canonicalize(self.path()).map_err(|error| format!("Could not canonicalize \"{}\". {error}", self.path()))?;
+		let canonical_path:&str = canonical_path.to_str().ok_or_else(|| format!("Could not canonicalize \"{}\". Path is not valid UTF-8.", self.path()))?;
+		let canonical_path:String = match canonical_path.strip_prefix(r"\\?\UNC\") {
+			Some(network_path) => r"\\".to_owned() + network_path,
+			None => canonical_path.strip_prefix(r"\\?\").unwrap_or(canonical_path).to_owned()
+		};
+		Ok(FileRef::new(&canonical_path))
+	}
+
+	/// Get the path without a trailing separator, unless the path is a root like '/' or 'C:/'. Used to compare dirs with and without trailing separator as equal.
+	pub(crate) fn comparable_path(&self) -> &str {
+		let path:&str = self.path();
+		if path.len() > 1 && path.ends_with(SEPARATOR) && !path.ends_with(&(DISK_SEPARATOR.to_owned() + SEPARATOR)) {
+			&path[..path.len() - SEPARATOR.len()]
+		} else {
+			path
+		}
+	}
+
+	/// Check if the path is relative and resolving it against the working dir only prefixes it, without '.', '..' or messy separators changing it.
+	fn is_plain_relative_path(&self) -> bool {
+		let path:&str = self.comparable_path();
+		self.is_relative_path() && !path.contains(INVALID_SEPARATOR) && path.split(SEPARATOR).all(|node| !node.is_empty() && node != "." && node != "..")
+	}
+
+	/// Get the path that equality, ordering and hashing are based on. Absolute paths are used as they are, relative paths are resolved against the working dir. When the working dir can not be read, relative paths are used as they are.
+	fn resolved_comparable_path(&self) -> Cow<'_, str> {
+		if self.is_absolute_path() {
+			return Cow::Borrowed(self.comparable_path());
+		}
+		match std::env::current_dir().ok().and_then(|working_dir| working_dir.to_str().map(FileRef::new)) {
+			Some(working_dir) => Cow::Owned(self.clone().absolute_from(&working_dir).comparable_path().to_owned()),
+			None => Cow::Borrowed(self.comparable_path())
+		}
+	}
+
+	/// Check if comparing with the other ref can skip resolving against the working dir. Two absolute paths, or two plain relative paths sharing the same working dir, order the same as their resolved paths.
+	fn compares_directly_with(&self, other:&FileRef) -> bool {
+		(self.is_absolute_path() && other.is_absolute_path()) || (self.is_plain_relative_path() && other.is_plain_relative_path())
+	}
+
 	/// Get a list of nodes in the path.
 	pub(crate) fn path_nodes(&self) -> Vec<&str> {
 		let mut parts:Vec<&str> = self.path().split(SEPARATOR).collect();
@@ -175,9 +435,9 @@ impl FileRef {
 		self.path().split(SEPARATOR).last().unwrap_or_default()
 	}
 
-	/// Check if the path is a relative or absolute path.
+	/// Check if the path is an absolute path. Paths starting with a separator, like Unix paths, and paths containing a disk separator, like 'C:/', are absolute.
 	pub fn is_absolute_path(&self) -> bool {
-		self.contains(DISK_SEPARATOR)
+		self.starts_with(SEPARATOR) || self.contains(DISK_SEPARATOR)
 	}
 
 	/// Check if the path is a relative or absolute path.
@@ -211,18 +471,17 @@ impl FileRef {
 
 	/// Check if the files exists.
 	pub fn exists(&self) -> bool {
-		std::path::Path::new(&self.path()).exists() && std::fs::metadata(&self.path()).is_ok()
+		self.stat_once().is_some()
 	}
 
 	/// Check if self is a dir.
 	pub fn is_dir(&self) -> bool {
-		// Check metadata if exists, otherwise check extension.
-		if self.exists() {
-			if let Ok(metadata) = std::fs::metadata(&self.path()) {
-				return metadata.is_dir();
-			}
+		// Check metadata if exists, otherwise check the hint or extension.
+		match (self.stat_once(), self.1) {
+			(Some(metadata), _) => metadata.is_dir(),
+			(None, Some(kind)) => kind == PathKind::Dir,
+			(None, None) => self.extension().map(|extension| extension.is_empty()).unwrap_or(true)
 		}
-		self.extension().map(|extension| extension.is_empty()).unwrap_or(true)
 	}
 
 	/// Check if self is a file.
@@ -230,6 +489,19 @@ impl FileRef {
 		!self.is_dir()
 	}
 	
+	/// Check if self is a symlink. Does not follow the symlink, so broken symlinks count as well.
+	pub fn is_symlink(&self) -> bool {
+		std::fs::symlink_metadata(self.path()).is_ok_and(|metadata| metadata.is_symlink())
+	}
+
+	/// Check if self is a named pipe (FIFO).
+	#[cfg(unix)]
+	pub fn is_fifo(&self) -> bool {
+		use std::os::unix::fs::FileTypeExt;
+
+		std::fs::metadata(self.path()).map(|metadata| metadata.file_type().is_fifo()).unwrap_or(false)
+	}
+
 	/// Check if the file can be accessed.
 	pub fn is_accessible(&self) -> bool {
 		if self.is_dir() { true } else { std::fs::File::open(&self.path()).is_ok() }
@@ -241,26 +513,167 @@ impl FileRef {
 	
 	/// Get the metadata of the file.
 	fn metadata(&self) -> Result<Metadata, Box<dyn Error>> {
-		if self.is_dir() {
-			Err(format!("Could not get metadata, file {self}, path is a directory.").into())
-		} else if !self.exists() {
-			Err(format!("Could not get metadata, file {self} does not exist").into())
-		} else {
-			Ok(std::fs::File::open(self.path())?.metadata()?)
+		match self.stat_once() {
+			Some(metadata) if metadata.is_dir() => Err(format!("Could not get metadata, file {self}, path is a directory.").into()),
+			Some(metadata) => Ok(metadata),
+			None => Err(format!("Could not get metadata, file {self} does not exist").into())
 		}
 	}
 
+	/// Get the metadata of the path, following symlinks, with a single stat call. Returns None if the path does not exist or can not be accessed. Methods that need both existence and type should stat through this once, rather than calling `exists` and `is_dir` separately.
+	fn stat_once(&self) -> Option<Metadata> {
+		#[cfg(test)]
+		crate::unit_test_support::record_syscall(crate::unit_test_support::Syscall::Stat);
+		std::fs::metadata(self.path()).ok()
+	}
+
 	/// Get the amount of bytes the file is.
 	pub fn bytes_size(&self) -> u64 {
 		if !self.exists() {
 			0
 		} else if self.is_dir() {
-			self.list_files_recurse().iter().map(|file| file.bytes_size()).sum()
+			self.scanner().include_files().recurse().map(|file| file.bytes_size()).sum()
 		} else {
 			self.metadata().map(|data| data.len()).unwrap_or(0)
 		}
 	}
 
+	/// Check if the file is at most the given amount of bytes, without reading its contents. Dirs are summed recursively like `bytes_size`, but stop as soon as the limit is exceeded.
+	pub fn within_size(&self, max_bytes:u64) -> Result<bool, Box<dyn Error>> {
+		if !self.exists() {
+			Err(format!("Could not check size of file \"{}\". File does not exist.", self.path()).into())
+		} else if self.is_dir() {
+			let mut total_bytes:u64 = 0;
+			for file in self.scanner().include_files().recurse() {
+				total_bytes += file.bytes_size();
+				if total_bytes > max_bytes {
+					return Ok(false);
+				}
+			}
+			Ok(true)
+		} else {
+			Ok(self.bytes_size() <= max_bytes)
+		}
+	}
+
+	/// Wait until the file reaches at least the given size, checking it once every poll interval. A nonexistent file counts as empty. Returns whether the size was reached before the timeout.
+	pub fn wait_until_size(&self, target_size:u64, timeout:Duration, poll_interval:Duration) -> Result<bool, Box<dyn Error>> {
+		use std::{ thread::sleep, time::Instant };
+
+		if self.exists() && self.is_dir() {
+			return Err(format!("Could not wait for size of dir \"{}\". Only able to wait for files.", self.path()).into());
+		}
+		let start_time:Instant = Instant::now();
+		loop {
+			if self.bytes_size() >= target_size {
+				return Ok(true);
+			}
+			let elapsed:Duration = start_time.elapsed();
+			if elapsed >= timeout {
+				return Ok(false);
+			}
+			sleep(poll_interval.min(timeout - elapsed));
+		}
+	}
+
+	/// Get the sector size of the device the file is on, which aligned reads should be a multiple of. Block devices are queried directly, for regular files on Unix the preferred block size of the file system is used.
+	#[cfg(feature="block_device")]
+	pub fn sector_size(&self) -> Result<u64, Box<dyn Error>> {
+		if !self.exists() {
+			Err(format!("Could not get sector size of \"{}\". Path does not exist.", self.path()).into())
+		} else {
+			self.platform_sector_size()
+		}
+	}
+
+	/// Get the sector size of the device the file is on.
+	#[cfg(all(feature="block_device", unix))]
+	fn platform_sector_size(&self) -> Result<u64, Box<dyn Error>> {
+		use std::{ fs::File, os::unix::fs::MetadataExt };
+
+		let file:File = File::open(self.path())?;
+		let metadata:Metadata = file.metadata()?;
+
+		#[cfg(target_os="linux")]
+		{
+			use std::{ io, os::{ fd::AsRawFd, unix::fs::FileTypeExt } };
+
+			if metadata.file_type().is_block_device() {
+				let mut sector_size:libc::c_int = 0;
+				if unsafe { libc::ioctl(file.as_raw_fd(), libc::BLKSSZGET, &mut sector_size) } != 0 {
+					return Err(io::Error::last_os_error().into());
+				}
+				return Ok(sector_size as u64);
+			}
+		}
+		Ok(metadata.blksize())
+	}
+
+	/// Get the sector size of the device the file is on.
+	#[cfg(all(feature="block_device", windows))]
+	fn platform_sector_size(&self) -> Result<u64, Box<dyn Error>> {
+		use std::{ ffi::OsStr, io, iter::once, os::windows::ffi::OsStrExt, ptr::null_mut };
+		use winapi::{ shared::minwindef::DWORD, um::fileapi::GetDiskFreeSpaceW };
+
+		let absolute_path:FileRef = self.clone().absolute();
+		let volume_root:String = absolute_path.path_nodes().first().copied().unwrap_or_default().to_owned() + "\\";
+		let volume_root:Vec<u16> = OsStr::new(&volume_root).encode_wide().chain(once(0)).collect();
+		let mut bytes_per_sector:DWORD = 0;
+		if unsafe { GetDiskFreeSpaceW(volume_root.as_ptr(), null_mut(), &mut bytes_per_sector, null_mut(), null_mut()) } == 0 {
+			return Err(io::Error::last_os_error().into());
+		}
+		Ok(bytes_per_sector as u64)
+	}
+
+	/// Get the amount of bytes available to the current user on the file system containing this path.
+	#[cfg(feature="disk_space")]
+	pub fn available_space(&self) -> Result<u64, Box<dyn Error>> {
+		Ok(self.disk_space()?.0)
+	}
+
+	/// Get the total amount of bytes on the file system containing this path.
+	#[cfg(feature="disk_space")]
+	pub fn total_space(&self) -> Result<u64, Box<dyn Error>> {
+		Ok(self.disk_space()?.1)
+	}
+
+	/// Get the available and total amount of bytes on the file system containing this path.
+	#[cfg(all(feature="disk_space", unix))]
+	fn disk_space(&self) -> Result<(u64, u64), Box<dyn Error>> {
+		use std::{ ffi::CString, io, mem::MaybeUninit };
+
+		if !self.exists() {
+			return Err(format!("Could not get disk space of \"{}\". Path does not exist.", self.path()).into());
+		}
+		let path:CString = CString::new(self.path())?;
+		let mut stats:MaybeUninit<libc::statvfs> = MaybeUninit::uninit();
+		if unsafe { libc::statvfs(path.as_ptr(), stats.as_mut_ptr()) } != 0 {
+			return Err(io::Error::last_os_error().into());
+		}
+		let stats:libc::statvfs = unsafe { stats.assume_init() };
+		#[allow(clippy::unnecessary_cast)] // Field types differ per platform.
+		Ok((stats.f_bavail as u64 * stats.f_frsize as u64, stats.f_blocks as u64 * stats.f_frsize as u64))
+	}
+
+	/// Get the available and total amount of bytes on the file system containing this path.
+	#[cfg(all(feature="disk_space", windows))]
+	fn disk_space(&self) -> Result<(u64, u64), Box<dyn Error>> {
+		use std::{ ffi::OsStr, io, iter::once, os::windows::ffi::OsStrExt, ptr::null_mut };
+		use winapi::um::{ fileapi::GetDiskFreeSpaceExW, winnt::ULARGE_INTEGER };
+
+		if !self.exists() {
+			return Err(format!("Could not get disk space of \"{}\". Path does not exist.", self.path()).into());
+		}
+		let dir:FileRef = if self.is_dir() { self.clone() } else { self.parent_dir()? };
+		let path:Vec<u16> = OsStr::new(dir.path()).encode_wide().chain(once(0)).collect();
+		let mut available:ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+		let mut total:ULARGE_INTEGER = unsafe { std::mem::zeroed() };
+		if unsafe { GetDiskFreeSpaceExW(path.as_ptr(), &mut available, &mut total, null_mut()) } == 0 {
+			return Err(io::Error::last_os_error().into());
+		}
+		Ok(unsafe { (*available.QuadPart(), *total.QuadPart()) })
+	}
+
 	/// Get the creation time of the file.
 	pub fn get_time_creation(&self) -> Result<SystemTime, Box<dyn Error>> {
 		match self.metadata()?.created() {
@@ -285,6 +698,42 @@ impl FileRef {
 		}
 	}
 
+	/// Set the last modification time of the file. Creation time cannot be set this way, as most Linux filesystems do not support changing it.
+	pub fn set_time_modification(&self, time:SystemTime) -> Result<(), Box<dyn Error>> {
+		self.set_times(FileTimes::new().set_modified(time))
+	}
+
+	/// Set the last accessed time of the file. Creation time cannot be set this way, as most Linux filesystems do not support changing it.
+	pub fn set_time_accessed(&self, time:SystemTime) -> Result<(), Box<dyn Error>> {
+		self.set_times(FileTimes::new().set_accessed(time))
+	}
+
+	/// Apply the given times to the file without changing its contents.
+	fn set_times(&self, times:FileTimes) -> Result<(), Box<dyn Error>> {
+		use std::fs::OpenOptions;
+
+		if !self.exists() {
+			Err(format!("Could not set time of file \"{}\". File does not exist.", self.path()).into())
+		} else if self.is_dir() {
+			Err(format!("Could not set time of dir \"{}\". Only able to set times of files.", self.path()).into())
+		} else {
+			OpenOptions::new().append(true).open(self.path())?.set_times(times)?;
+			Ok(())
+		}
+	}
+
+	/// Check if this file was modified more recently than the other file. A nonexistent other file counts as older.
+	pub fn is_newer_than(&self, other:&FileRef) -> Result<bool, Box<dyn Error>> {
+		let modified:SystemTime = self.get_time_modification()?;
+		Ok(!other.exists() || modified > other.get_time_modification()?)
+	}
+
+	/// Check if this file was modified less recently than the other file. A nonexistent other file counts as older.
+	pub fn is_older_than(&self, other:&FileRef) -> Result<bool, Box<dyn Error>> {
+		let modified:SystemTime = self.get_time_modification()?;
+		Ok(other.exists() && modified < other.get_time_modification()?)
+	}
+
 	/// Get the file's permissions.
 	pub fn permissions(&self) -> Result<Permissions, Box<dyn Error>> {
 		Ok(self.metadata()?.permissions())
@@ -310,6 +759,150 @@ impl FileRef {
 		}
 	}
 
+	/// Read the contents of the file as a string, replacing invalid UTF-8 sequences with the replacement character rather than erroring.
+	pub fn read_lossy(&self) -> Result<String, Box<dyn Error>> {
+		Ok(String::from_utf8_lossy(&self.read_bytes()?).into_owned())
+	}
+
+	/// Read the contents of the file as a string. Refs created with `new_embedded` borrow their embedded contents without allocating, all others are read from disk.
+	pub fn read_cow(&self) -> Result<Cow<'static, str>, Box<dyn Error>> {
+		match self.0 {
+			FilePath::Embedded(_, contents) => Ok(Cow::Borrowed(contents)),
+			_ => Ok(Cow::Owned(self.read()?))
+		}
+	}
+
+	/// Read the file line by line without loading it fully. The lines do not include their line ending.
+	pub fn read_lines(&self) -> Result<impl Iterator<Item = Result<String, Box<dyn Error>>>, Box<dyn Error>> {
+		use std::{ fs::File, io::{ BufRead, BufReader } };
+
+		if self.is_dir() {
+			Err(format!("Could not read dir \"{}\". Only able to read files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			Ok(BufReader::new(File::open(self.path())?).lines().map(|line| line.map_err(|error| error.into())))
+		}
+	}
+
+	/// Read only the first n lines of the file, without reading the rest of it. Returns fewer lines if the file is shorter.
+	pub fn read_first_lines(&self, n:usize) -> Result<Vec<String>, Box<dyn Error>> {
+		self.read_lines()?.take(n).collect()
+	}
+
+	/// Find all lines in the file containing the given pattern, paired with their line number starting at 1. Reads the file line by line rather than loading it fully.
+	pub fn grep(&self, pattern:&str) -> Result<Vec<(usize, String)>, Box<dyn Error>> {
+		self.grep_lines(|line| line.contains(pattern))
+	}
+
+	/// Find all lines in the file matching the given regex, paired with their line number starting at 1. Reads the file line by line rather than loading it fully. Errors if the regex does not compile.
+	#[cfg(feature="regex")]
+	pub fn grep_regex(&self, pattern:&str) -> Result<Vec<(usize, String)>, Box<dyn Error>> {
+		let regex:regex::Regex = regex::Regex::new(pattern)?;
+		self.grep_lines(|line| regex.is_match(line))
+	}
+
+	/// Find all lines in the file accepted by the given filter, paired with their line number starting at 1.
+	fn grep_lines<T:Fn(&str) -> bool>(&self, filter:T) -> Result<Vec<(usize, String)>, Box<dyn Error>> {
+		let mut matches:Vec<(usize, String)> = Vec::new();
+		for (index, line) in self.read_lines()?.enumerate() {
+			let line:String = line?;
+			if filter(&line) {
+				matches.push((index + 1, line));
+			}
+		}
+		Ok(matches)
+	}
+
+	/// Check if the file looks like a binary file rather than text, by looking for NUL bytes in its first 8 KiB.
+	pub fn is_binary(&self) -> Result<bool, Box<dyn Error>> {
+		use std::fs::File;
+
+		if self.is_dir() {
+			Err(format!("Could not read dir \"{}\". Only able to read files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			let mut buffer:Vec<u8> = Vec::with_capacity(8192);
+			File::open(self.path())?.take(8192).read_to_end(&mut buffer)?;
+			Ok(buffer.contains(&0))
+		}
+	}
+
+	/// Count the lines in the file without loading it into memory. A final line without a trailing newline is counted as well.
+	pub fn lines_count(&self) -> Result<usize, Box<dyn Error>> {
+		use std::{ fs::File, io::{ BufRead, BufReader } };
+
+		if self.is_dir() {
+			Err(format!("Could not read dir \"{}\". Only able to read files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			let mut reader:BufReader<File> = BufReader::new(File::open(self.path())?);
+			let mut lines_count:usize = 0;
+			let mut last_byte:Option<u8> = None;
+			loop {
+				let buffer:&[u8] = reader.fill_buf()?;
+				if buffer.is_empty() {
+					break;
+				}
+				lines_count += buffer.iter().filter(|byte| **byte == b'\n').count();
+				last_byte = buffer.last().copied();
+				let buffer_len:usize = buffer.len();
+				reader.consume(buffer_len);
+			}
+			if last_byte.is_some_and(|byte| byte != b'\n') {
+				lines_count += 1;
+			}
+			Ok(lines_count)
+		}
+	}
+
+	/// Read the contents of the file as a string with all line endings converted to '\n'.
+	pub fn read_normalized_newlines(&self) -> Result<String, Box<dyn Error>> {
+		Ok(LineEnding::normalize(&self.read()?))
+	}
+
+	/// Read the contents of the file as a string, decompressing it first if it is gzipped. Gzipped files are recognized by a 'gz' extension or the gzip magic bytes.
+	#[cfg(feature="flate2")]
+	pub fn read_auto(&self) -> Result<String, Box<dyn Error>> {
+		use std::io::Read;
+		use flate2::read::GzDecoder;
+
+		let bytes:Vec<u8> = self.read_bytes()?;
+		if self.extension() == Some("gz") || bytes.starts_with(&[0x1F, 0x8B]) {
+			let mut contents:String = String::new();
+			GzDecoder::new(bytes.as_slice()).read_to_string(&mut contents)?;
+			Ok(contents)
+		} else {
+			Ok(String::from_utf8(bytes)?)
+		}
+	}
+
+	/// Read the contents of the file as a string in the given text encoding. A byte order mark at the start of the file is stripped and, for the UTF encodings, overrides the given encoding. Latin-1 files have no byte order mark, as every byte is a valid character.
+	#[cfg(feature="encoding")]
+	pub fn read_with_encoding(&self, encoding:Encoding) -> Result<String, Box<dyn Error>> {
+		let bytes:Vec<u8> = self.read_bytes()?;
+		let (encoding, bytes):(Encoding, &[u8]) = match encoding {
+			Encoding::Latin1 => (encoding, &bytes),
+			_ if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) => (Encoding::Utf8, &bytes[3..]),
+			_ if bytes.starts_with(&[0xFF, 0xFE]) => (Encoding::Utf16Le, &bytes[2..]),
+			_ if bytes.starts_with(&[0xFE, 0xFF]) => (Encoding::Utf16Be, &bytes[2..]),
+			_ => (encoding, &bytes)
+		};
+		match encoding {
+			Encoding::Utf8 => Ok(String::from_utf8(bytes.to_vec())?),
+			Encoding::Latin1 => Ok(bytes.iter().map(|byte| *byte as char).collect()),
+			Encoding::Utf16Le | Encoding::Utf16Be => {
+				if bytes.len() % 2 != 0 {
+					return Err(format!("Could not read file \"{}\" as UTF-16. File has an odd number of bytes.", self.path()).into());
+				}
+				let units:Vec<u16> = bytes.chunks_exact(2).map(|pair| if encoding == Encoding::Utf16Le { u16::from_le_bytes([pair[0], pair[1]]) } else { u16::from_be_bytes([pair[0], pair[1]]) }).collect();
+				Ok(String::from_utf16(&units)?)
+			}
+		}
+	}
+
 	/// Read the contents of the file as bytes.
 	pub fn read_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>> {
 		use std::{ fs::File, io::Read };
@@ -326,7 +919,7 @@ impl FileRef {
 		}
 	}
 	
-	/// Read a specific range of bytes from the file.
+	/// Read a specific range of bytes from the file. An end of `u64::MAX` reads up to the end of the file.
 	pub fn read_range(&self, start:u64, end:u64) -> Result<Vec<u8>, Box<dyn Error>> {
 		use std::{ fs::File, io::{ Read, Seek, SeekFrom } };
 
@@ -335,6 +928,10 @@ impl FileRef {
 		} else if !self.exists() {
 			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
 		} else {
+			let end:u64 = if end == u64::MAX { self.bytes_size() } else { end };
+			if start > end {
+				return Err(format!("Could not read range {start}..{end} from file \"{}\". Range start is past its end.", self.path()).into());
+			}
 			let mut file:File = File::open(self.path())?;
 			let mut buffer:Vec<u8> = vec![0; (end - start) as usize];
 			file.seek(SeekFrom::Start(start))?;
@@ -343,6 +940,189 @@ impl FileRef {
 		}
 	}
 
+	/// Read a specific range of bytes from the file as a string. Errors with the offending position if the range starts or ends in the middle of a multi-byte character.
+	pub fn read_range_str(&self, start:u64, end:u64) -> Result<String, Box<dyn Error>> {
+		match String::from_utf8(self.read_range(start, end)?) {
+			Ok(text) => Ok(text),
+			Err(error) => {
+				let utf8_error:std::str::Utf8Error = error.utf8_error();
+				let bytes:&[u8] = error.as_bytes();
+				let is_continuation_byte = |byte:u8| byte & 0xC0 == 0x80;
+				if utf8_error.valid_up_to() == 0 && !bytes.is_empty() && is_continuation_byte(bytes[0]) {
+					Err(format!("Could not read range {start}..{end} of file \"{}\" as text. Start {start} is in the middle of a multi-byte character.", self.path()).into())
+				} else if utf8_error.error_len().is_none() {
+					Err(format!("Could not read range {start}..{end} of file \"{}\" as text. End {end} is in the middle of a multi-byte character.", self.path()).into())
+				} else {
+					Err(format!("Could not read range {start}..{end} of file \"{}\" as text. Invalid UTF-8 at byte {}.", self.path(), start + utf8_error.valid_up_to() as u64).into())
+				}
+			}
+		}
+	}
+
+	/// Read all bytes from the given offset to the end of the file.
+	pub fn read_from(&self, start:u64) -> Result<Vec<u8>, Box<dyn Error>> {
+		self.read_range(start, u64::MAX)
+	}
+
+	/// Read a single byte at the given offset. Returns None if the offset is at or past the end of the file.
+	pub fn byte_at(&self, offset:u64) -> Result<Option<u8>, Box<dyn Error>> {
+		use std::fs::File;
+
+		if self.is_dir() {
+			Err(format!("Could not read dir \"{}\". Only able to read files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			let file:File = File::open(self.path())?;
+			let mut buffer:[u8; 1] = [0];
+			#[cfg(unix)]
+			let bytes_read:usize = std::os::unix::fs::FileExt::read_at(&file, &mut buffer, offset)?;
+			#[cfg(windows)]
+			let bytes_read:usize = std::os::windows::fs::FileExt::seek_read(&file, &mut buffer, offset)?;
+			#[cfg(not(any(unix, windows)))]
+			let bytes_read:usize = {
+				(&file).seek(std::io::SeekFrom::Start(offset))?;
+				(&file).read(&mut buffer)?
+			};
+			Ok(if bytes_read == 0 { None } else { Some(buffer[0]) })
+		}
+	}
+
+	/// Get the end offset of a range of the given length, erroring with `InvalidInput` if it does not fit in a u64.
+	fn range_end(&self, start:u64, len:u64) -> Result<u64, Box<dyn Error>> {
+		use std::io::{ self, ErrorKind };
+
+		start.checked_add(len).ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, format!("Could not use range of {len} bytes at offset {start} in file \"{}\". Range end overflows.", self.path())).into())
+	}
+
+	/// Read an exact amount of bytes at the given offset, erroring if the file ends before all bytes could be read.
+	fn read_exact_at<const SIZE:usize>(&self, offset:u64) -> Result<[u8; SIZE], Box<dyn Error>> {
+		let end:u64 = self.range_end(offset, SIZE as u64)?;
+		if self.is_file() && self.bytes_size() < end {
+			Err(format!("Could not read {SIZE} bytes at offset {offset} from file \"{}\". File ends at {}.", self.path(), self.bytes_size()).into())
+		} else {
+			let bytes:Vec<u8> = self.read_range(offset, end)?;
+			Ok(bytes.try_into().map_err(|_| format!("Could not read {SIZE} bytes at offset {offset} from file \"{}\".", self.path()))?)
+		}
+	}
+
+	/// Get the ranges of the file that hold data, as start and end offsets. The holes of a sparse file lie between the ranges. File systems without hole support report the whole file as a single range.
+	#[cfg(all(feature="sparse", target_os="linux"))]
+	pub fn sparse_ranges(&self) -> Result<Vec<(u64, u64)>, Box<dyn Error>> {
+		use std::{ io, os::fd::AsRawFd };
+
+		let file:std::fs::File = self.open_read()?;
+		let size:i64 = file.metadata()?.len() as i64;
+		let mut ranges:Vec<(u64, u64)> = Vec::new();
+		let mut offset:i64 = 0;
+		while offset < size {
+			let data_start:i64 = unsafe { libc::lseek(file.as_raw_fd(), offset, libc::SEEK_DATA) };
+			if data_start < 0 {
+				let error:io::Error = io::Error::last_os_error();
+				if error.raw_os_error() == Some(libc::ENXIO) {
+					break; // No more data after offset, the rest of the file is a hole.
+				}
+				return Err(error.into());
+			}
+			let data_end:i64 = unsafe { libc::lseek(file.as_raw_fd(), data_start, libc::SEEK_HOLE) };
+			if data_end < 0 {
+				return Err(io::Error::last_os_error().into());
+			}
+			ranges.push((data_start as u64, data_end as u64));
+			offset = data_end;
+		}
+		Ok(ranges)
+	}
+
+	/// Open the file as a buffered reader that can be read and seeked freely.
+	pub fn reader(&self) -> Result<impl Read + Seek, Box<dyn Error>> {
+		Ok(std::io::BufReader::new(self.open_read()?))
+	}
+
+	/// Open the file for reading and return the raw std `File` handle.
+	pub fn open_read(&self) -> Result<std::fs::File, Box<dyn Error>> {
+		if self.is_dir() {
+			Err(format!("Could not read dir \"{}\". Only able to read files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			Ok(std::fs::File::open(self.path())?)
+		}
+	}
+
+	/// Read a range of bytes from the file using reads aligned to the sector size, as raw block devices require. The read range is extended to sector boundaries and the result is trimmed back to the requested range.
+	#[cfg(feature="block_device")]
+	pub fn read_aligned(&self, offset:u64, len:u64) -> Result<Vec<u8>, Box<dyn Error>> {
+		use std::{ fs::File, io::{ Read, Seek, SeekFrom } };
+
+		if self.is_dir() {
+			Err(format!("Could not read dir \"{}\". Only able to read files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			let end:u64 = self.range_end(offset, len)?;
+			if end > self.bytes_size() {
+				return Err(format!("Could not read file \"{}\". Range {}..{} exceeds the file size.", self.path(), offset, end).into());
+			}
+			let sector_size:u64 = self.sector_size()?.max(1);
+			let aligned_start:u64 = offset / sector_size * sector_size;
+			let aligned_end:u64 = end.div_ceil(sector_size) * sector_size;
+			let mut file:File = File::open(self.path())?;
+			let mut buffer:Vec<u8> = Vec::with_capacity((aligned_end - aligned_start) as usize);
+			file.seek(SeekFrom::Start(aligned_start))?;
+			file.take(aligned_end - aligned_start).read_to_end(&mut buffer)?;
+
+			let trim_start:usize = (offset - aligned_start) as usize;
+			if buffer.len() < trim_start + len as usize {
+				return Err(format!("Could not read file \"{}\". Range {}..{} exceeds the file size.", self.path(), offset, offset + len).into());
+			}
+			buffer.truncate(trim_start + len as usize);
+			buffer.drain(..trim_start);
+			Ok(buffer)
+		}
+	}
+
+	/// Hash the contents of the file with SHA-256, reading it in chunks. Returns the digest as lowercase hex.
+	#[cfg(feature="hashing")]
+	pub fn hash_sha256(&self) -> Result<String, Box<dyn Error>> {
+		use std::fs::File;
+		use sha2::{ Digest, Sha256 };
+
+		if self.is_dir() {
+			Err(format!("Could not read dir \"{}\". Only able to read files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not read file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			let mut hasher:Sha256 = Sha256::new();
+			let mut file:File = File::open(self.path())?;
+			let mut buffer:[u8; 8192] = [0; 8192];
+			loop {
+				let bytes_read:usize = file.read(&mut buffer)?;
+				if bytes_read == 0 {
+					break;
+				}
+				hasher.update(&buffer[..bytes_read]);
+			}
+			Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+		}
+	}
+
+	/// Parse the file as json and get the single value at the given RFC 6901 pointer, like "/server/port".
+	#[cfg(feature="serde")]
+	pub fn read_json_pointer(&self, pointer:&str) -> Result<serde_json::Value, Box<dyn Error>> {
+		let mut json:serde_json::Value = serde_json::from_str(&self.read()?)?;
+		match json.pointer_mut(pointer) {
+			Some(value) => Ok(value.take()),
+			None => Err(format!("Could not read json pointer \"{pointer}\" from file \"{}\". Pointer does not resolve.", self.path()).into())
+		}
+	}
+
+	/// Parse the file as json into the given type.
+	#[cfg(feature="json")]
+	pub fn read_json<T:serde::de::DeserializeOwned>(&self) -> Result<T, Box<dyn Error>> {
+		Ok(serde_json::from_str(&self.read()?)?)
+	}
+
 
 
 	/* FILE WRITING METHODS */
@@ -355,6 +1135,15 @@ impl FileRef {
 		Ok(())
 	}
 
+	/// Create the file if it does not exist, otherwise set its modification time to now without changing its contents.
+	pub fn touch(&self) -> Result<(), Box<dyn Error>> {
+		if !self.exists() {
+			self.create_file()
+		} else {
+			self.set_time_modification(SystemTime::now())
+		}
+	}
+
 	/// If the parent dir does not exist, create it.
 	pub fn guarantee_parent_dir(&self) -> Result<(), Box<dyn Error>> {
 		let parent_dir:FileRef = self.parent_dir()?;
@@ -365,6 +1154,21 @@ impl FileRef {
 		Ok(())
 	}
 
+	/// Make sure the parent dir exists, then run the given function on this path.
+	pub fn with_parent_dir<R>(&self, action:impl FnOnce(&FileRef) -> Result<R, Box<dyn Error>>) -> Result<R, Box<dyn Error>> {
+		self.guarantee_parent_dir()?;
+		action(self)
+	}
+
+	/// Make sure the parent dirs of all given paths exist. Resolves the working dir only once for all relative paths, rather than once per path.
+	pub fn guarantee_parent_dirs(paths:&[FileRef]) -> Result<(), Box<dyn Error>> {
+		let working_dir:FileRef = FileRef::working_dir();
+		for path in paths {
+			path.clone().absolute_from(&working_dir).guarantee_parent_dir()?;
+		}
+		Ok(())
+	}
+
 	/// Create the file.
 	pub fn create(&self) -> Result<(), Box<dyn Error>> {
 		if self.is_dir() {
@@ -378,26 +1182,65 @@ impl FileRef {
 	pub fn create_file(&self) -> Result<(), Box<dyn Error>> {
 		use std::fs::File;
 
-		let is_dir:bool = self.is_dir();
-		if self.exists() {
-			Err(format!("Could not create {} \"{}\". {} already exists.", if is_dir { "dir" } else { "file" }, self.path(), if is_dir { "Dir" } else { "File" }).into())
+		if let Some(metadata) = self.stat_once() {
+			let is_dir:bool = metadata.is_dir();
+			Err(format!("Could not create {} \"{}\". {} already exists.", if is_dir { "dir" } else { "file" }, self.path(), if is_dir { "Dir" } else { "File" }).into())
+		} else {
+			self.guarantee_parent_dir()?;
+			File::create(&self.path())?;
+			Ok(())
+		}
+	}
+
+	/// Create this path specifically as a dir.
+	pub fn create_dir(&self) -> Result<(), Box<dyn Error>> {
+		use std::fs::create_dir;
+
+		if let Some(metadata) = self.stat_once() {
+			let is_dir:bool = metadata.is_dir();
+			Err(format!("Could not create {} \"{}\". {} already exists.", if is_dir { "dir" } else { "file" }, self.path(), if is_dir { "Dir" } else { "File" }).into())
+		} else {
+			self.guarantee_parent_dir()?;
+			create_dir(self.path()).map_err(|error| error.into())
+		}
+	}
+
+	/// Create the file and write the data to it in a single open, which fails if the file already exists, even when another process creates it at the same moment. Creates the parent dir if it does not exist yet.
+	pub fn create_new_with(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
+		use std::{ fs::OpenOptions, io::{ ErrorKind, Write } };
+
+		self.guarantee_parent_dir()?;
+		match OpenOptions::new().create_new(true).write(true).open(self.path()) {
+			Ok(mut file) => Ok(file.write_all(data)?),
+			Err(error) if error.kind() == ErrorKind::AlreadyExists => Err(format!("Could not create file \"{}\". File already exists.", self.path()).into()),
+			Err(error) => Err(error.into())
+		}
+	}
+
+	/// Create this path as a dir together with all missing parent dirs in one call. Does nothing if the dir already exists.
+	pub fn create_all(&self) -> Result<(), Box<dyn Error>> {
+		if self.stat_once().is_some_and(|metadata| !metadata.is_dir()) {
+			Err(format!("Could not create dir \"{}\". File already exists.", self.path()).into())
 		} else {
-			self.guarantee_parent_dir()?;
-			File::create(&self.path())?;
-			Ok(())
+			std::fs::create_dir_all(self.path()).map_err(|error| format!("Could not create dir \"{}\". {error}", self.path()).into())
 		}
 	}
 
-	/// Create this path specifically as a dir.
-	pub fn create_dir(&self) -> Result<(), Box<dyn Error>> {
-		use std::fs::create_dir;
+	/// Create this path as a named pipe (FIFO) with the given permission mode, like 0o644.
+	#[cfg(all(unix, feature="fifo"))]
+	pub fn create_fifo(&self, mode:u32) -> Result<(), Box<dyn Error>> {
+		use std::{ ffi::CString, io };
 
-		let is_dir:bool = self.is_dir();
 		if self.exists() {
-			Err(format!("Could not create {} \"{}\". {} already exists.", if is_dir { "dir" } else { "file" }, self.path(), if is_dir { "Dir" } else { "File" }).into())
+			Err(format!("Could not create fifo \"{}\". Path already exists.", self.path()).into())
 		} else {
 			self.guarantee_parent_dir()?;
-			create_dir(self.path()).map_err(|error| error.into())
+			let path:CString = CString::new(self.path())?;
+			if unsafe { libc::mkfifo(path.as_ptr(), mode as libc::mode_t) } == 0 {
+				Ok(())
+			} else {
+				Err(io::Error::last_os_error().into())
+			}
 		}
 	}
 
@@ -420,6 +1263,17 @@ impl FileRef {
 		}
 	}
 
+	/// Write a string to the file, converting all of its line endings to the given line ending.
+	pub fn write_with_line_ending(&self, text:&str, ending:LineEnding) -> Result<(), Box<dyn Error>> {
+		self.write(LineEnding::normalize(text).replace('\n', ending.as_str()))
+	}
+
+	/// Write the value to the file as pretty-printed json. Creates the parent dirs if they do not exist yet.
+	#[cfg(feature="json")]
+	pub fn write_json<T:serde::Serialize>(&self, value:&T) -> Result<(), Box<dyn Error>> {
+		self.write(serde_json::to_string_pretty(value)?)
+	}
+
 	/// Write bytes to the file.
 	pub fn write_bytes(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
 		self._write_bytes(data, false)
@@ -430,6 +1284,51 @@ impl FileRef {
 		self._write_bytes(data, true)
 	}
 
+	/// Open the file for writing and return the raw std `File` handle, positioned at the start of the file. Creates the file and its parent dir if they do not exist yet. When truncating, the existing contents are removed.
+	pub fn open_write(&self, truncate:bool) -> Result<std::fs::File, Box<dyn Error>> {
+		use std::fs::OpenOptions;
+
+		if self.is_dir() {
+			Err(format!("Could not write to dir \"{}\". Only able to write to files.", self.path()).into())
+		} else {
+			self.guarantee_parent_dir()?;
+			Ok(OpenOptions::new().create(true).write(true).truncate(truncate).open(self.path())?)
+		}
+	}
+
+	/// Write bytes to the file, restoring the permissions the file had before writing. Files that did not exist yet get the default permissions.
+	pub fn write_bytes_preserving_mode(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
+		let permissions:Option<Permissions> = if self.exists() && !self.is_dir() { Some(self.permissions()?) } else { None };
+		self.write_bytes(data)?;
+		if let Some(permissions) = permissions {
+			std::fs::set_permissions(self.path(), permissions)?;
+		}
+		Ok(())
+	}
+
+	/// Write bytes to the file only if the file does not exist yet or was last modified before the given source modification time. The modification time of the written file is set to the source modification time. Returns whether or not the file was written.
+	pub fn write_if_newer(&self, data:&[u8], source_mtime:SystemTime) -> Result<bool, Box<dyn Error>> {
+		use std::fs::OpenOptions;
+
+		if self.exists() && self.get_time_modification()? >= source_mtime {
+			Ok(false)
+		} else {
+			self.write_bytes(data)?;
+			OpenOptions::new().write(true).open(self.path())?.set_modified(source_mtime)?;
+			Ok(true)
+		}
+	}
+
+	/// Write the same bytes to all given files, creating them and their parent dirs where needed. Does not stop on failure, but returns the error of every target that could not be written.
+	pub fn write_to_all(targets:&[FileRef], data:&[u8]) -> Result<(), FileErrors> {
+		let errors:FileErrors = targets.iter().filter_map(|target| target.write_bytes(data).err().map(|error| (target.clone(), error))).collect();
+		if errors.is_empty() {
+			Ok(())
+		} else {
+			Err(errors)
+		}
+	}
+
 	/// Write bytes to the file.
 	fn _write_bytes(&self, data:&[u8], await_finish:bool) -> Result<(), Box<dyn Error>> {
 		use std::{ fs::{ File, OpenOptions }, io::Write };
@@ -476,28 +1375,175 @@ impl FileRef {
 		}
 	}
 
+	/// Write bytes to the file through a temporary sibling file that replaces the file once fully written, so a crash mid-write never leaves a partially written file. The temporary file is removed if writing fails. Keeps the permissions of an existing file and writes through symlinks to their target.
+	pub fn write_atomic(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
+		self._write_atomic(data, false)
+	}
+
+	/// Write bytes to the file through a temporary sibling file that replaces the file once fully written. Syncs the temporary file to disk before replacing the file and syncs the parent dir afterwards, so the write is not lost on a crash. Windows does not support syncing dirs, so there only the file itself is synced.
+	pub fn write_atomic_durable(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
+		self._write_atomic(data, true)
+	}
+
+	/// Write bytes to the file through a temporary sibling file that replaces the file once fully written.
+	fn _write_atomic(&self, data:&[u8], durable:bool) -> Result<(), Box<dyn Error>> {
+		use std::io::Write;
+
+		if self.is_dir() {
+			return Err(format!("Could not write to dir \"{}\". Only able to write to files.", self.path()).into());
+		}
+		self._replace_atomic(durable, |file| Ok(file.write_all(data)?))
+	}
+
+	/// Replace the file with a temporary sibling file filled by the given function. Writes through symlinks instead of replacing them and keeps the permissions of the original file. The temporary file is removed if filling or replacing fails.
+	fn _replace_atomic<T:FnOnce(&mut std::fs::File) -> Result<(), Box<dyn Error>>>(&self, durable:bool, fill:T) -> Result<(), Box<dyn Error>> {
+		use std::{ fs::{ File, rename }, io::Write };
+
+		// Write through symlinks instead of replacing them.
+		if self.is_symlink() {
+			return self.canonicalize()?._replace_atomic(durable, fill);
+		}
+
+		// Write and replace, keeping the permissions of the original file.
+		self.guarantee_parent_dir()?;
+		let permissions:Option<Permissions> = if self.exists() { Some(self.permissions()?) } else { None };
+		let temp_file:FileRef = self.temp_sibling()?;
+		let result:Result<(), Box<dyn Error>> = (|| {
+			let mut file:File = File::create(temp_file.path())?;
+			if let Some(permissions) = permissions {
+				file.set_permissions(permissions)?;
+			}
+			fill(&mut file)?;
+			file.flush()?;
+			if durable {
+				file.sync_all()?;
+			}
+			rename(temp_file.path(), self.path())?;
+			Ok(())
+		})();
+		if let Err(error) = result {
+			if temp_file.exists() {
+				temp_file.delete()?;
+			}
+			return Err(error);
+		}
+
+		// Make sure the rename itself is stored.
+		#[cfg(unix)]
+		if durable {
+			File::open(self.parent_dir()?.path())?.sync_all()?;
+		}
+		Ok(())
+	}
+
+	/// Replace a range of bytes in the file with data of a different length, shifting the rest of the file along. Writes the result to a temporary sibling file which replaces the original once complete, keeping its permissions and writing through symlinks.
+	pub fn splice(&self, start:u64, remove_len:u64, insert:&[u8]) -> Result<(), Box<dyn Error>> {
+		use std::{ fs::File, io::{ copy, Read, Seek, SeekFrom, Write } };
+
+		if self.is_dir() {
+			Err(format!("Could not write to dir \"{}\". Only able to write to files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not write to file \"{}\". File does not exist.", self.path()).into())
+		} else if self.range_end(start, remove_len)? > self.bytes_size() {
+			Err(format!("Could not splice file \"{}\". Range {}..{} exceeds the file size.", self.path(), start, start + remove_len).into())
+		} else {
+			self._replace_atomic(false, |target| {
+				let mut source:File = File::open(self.path())?;
+				copy(&mut (&mut source).take(start), target)?;
+				target.write_all(insert)?;
+				source.seek(SeekFrom::Start(start + remove_len))?;
+				copy(&mut source, target)?;
+				Ok(())
+			})
+		}
+	}
+
+	/// Get a hidden file next to this file that can be used as temporary file while writing.
+	pub(crate) fn temp_sibling(&self) -> Result<FileRef, Box<dyn Error>> {
+		Ok(self.parent_dir()? + &format!("/.{}.tmp", self.name()))
+	}
+
 	/// Append a string to the file. Writes it to the file on disk.
 	pub fn append(&self, contents:String) -> Result<(), Box<dyn Error>> {
-		self._append_bytes(contents.as_bytes(), false)
+		self._append_bytes(contents.as_bytes(), false, false)
 	}
 
-	/// Append a string to the file and wait until the file has finished. Writes it to the file on disk.
+	/// Append a string to the file and wait until the file has finished. This flushes the write to the OS, which may still hold it in its cache, use `append_bytes_synced` to make sure it reaches the disk.
 	pub fn append_await(&self, contents:String) -> Result<(), Box<dyn Error>> {
-		self._append_bytes(contents.as_bytes(), true)
+		self._append_bytes(contents.as_bytes(), true, false)
 	}
 
-	/// Append bytes to the file.
+	/// Append bytes to the file. The file is opened in append mode and the bytes are written with a single `write_all`, so other appenders can not overwrite them, though large writes may still be split up by the OS.
 	pub fn append_bytes(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
-		self._append_bytes(data, false)
+		self._append_bytes(data, false, false)
 	}
 
 	/// Append bytes to the file and wait until the file has finished.
 	pub fn append_bytes_await(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
-		self._append_bytes(data, true)
+		self._append_bytes(data, true, false)
+	}
+
+	/// Append bytes to the file and sync them to disk before returning, so they survive a power failure. Unlike the `_await` variants, which only flush the write out of the program's buffers to the OS, this waits until the OS has written the file to the storage device, which is considerably slower.
+	pub fn append_bytes_synced(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
+		self._append_bytes(data, true, true)
+	}
+
+	/// Append bytes to the file, creating the file and its parent dir if they do not exist yet. Creates and opens the file in a single call.
+	pub fn append_bytes_create(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
+		self._append_bytes_create(data, false)
+	}
+
+	/// Append bytes to the file in a single write call on an append-mode handle, so concurrent appenders can not interleave with them. Creates the file and its parent dir if they do not exist yet. Errors if the OS wrote only part of the bytes, on Unix writes up to `PIPE_BUF` bytes (at least 512) are never split.
+	pub fn append_atomic(&self, data:&[u8]) -> Result<(), Box<dyn Error>> {
+		use std::{ fs::OpenOptions, io::Write };
+
+		if self.is_dir() {
+			Err(format!("Could not append to dir \"{}\". Only able to append to files.", self.path()).into())
+		} else {
+			self.parent_dir()?.create_all()?; // Does not fail when another appender creates the dir at the same time.
+			let bytes_written:usize = OpenOptions::new().create(true).append(true).open(self.path())?.write(data)?;
+			if bytes_written == data.len() {
+				Ok(())
+			} else {
+				Err(format!("Could not append to file \"{}\" atomically. Only {bytes_written} of {} bytes were written.", self.path(), data.len()).into())
+			}
+		}
+	}
+
+	/// Append a line to the file, followed by '\n'. Creates the file and its parent dir if they do not exist yet.
+	pub fn append_line(&self, line:&str) -> Result<(), Box<dyn Error>> {
+		self._append_bytes_create((line.to_owned() + LineEnding::Lf.as_str()).as_bytes(), false)
+	}
+
+	/// Append a line to the file, followed by '\n', and wait until the file has finished. Creates the file and its parent dir if they do not exist yet.
+	pub fn append_line_await(&self, line:&str) -> Result<(), Box<dyn Error>> {
+		self._append_bytes_create((line.to_owned() + LineEnding::Lf.as_str()).as_bytes(), true)
+	}
+
+	/// Append a line to the file, followed by the given line ending. Creates the file and its parent dir if they do not exist yet.
+	pub fn append_line_with_ending(&self, line:&str, ending:LineEnding) -> Result<(), Box<dyn Error>> {
+		self._append_bytes_create((line.to_owned() + ending.as_str()).as_bytes(), false)
+	}
+
+	/// Append bytes to the file, creating the file and its parent dir if they do not exist yet.
+	fn _append_bytes_create(&self, data:&[u8], await_finish:bool) -> Result<(), Box<dyn Error>> {
+		use std::{ fs::{ File, OpenOptions }, io::Write };
+
+		if self.is_dir() {
+			Err(format!("Could not append to dir \"{}\". Only able to append to files.", self.path()).into())
+		} else {
+			self.guarantee_parent_dir()?;
+			let mut file:File = OpenOptions::new().create(true).append(true).open(self.path())?;
+			file.write_all(data)?;
+			if await_finish {
+				file.flush()?;
+			}
+			Ok(())
+		}
 	}
 
 	/// Append bytes to the file.
-	fn _append_bytes(&self, data:&[u8], await_finish:bool) -> Result<(), Box<dyn Error>> {
+	fn _append_bytes(&self, data:&[u8], await_finish:bool, durable:bool) -> Result<(), Box<dyn Error>> {
 		use std::{ fs::{ File, OpenOptions }, io::Write };
 
 		if self.is_dir() {
@@ -511,40 +1557,298 @@ impl FileRef {
 			if await_finish {
 				file.flush()?;
 			}
+			if durable {
+				file.sync_all()?;
+			}
 			Ok(())
 		}
 	}
 
 
 
+	/// Lock the file exclusively for this process, waiting until other locks are released. The lock is released when the returned guard is dropped.
+	pub fn lock_exclusive(&self) -> Result<FileLock, Box<dyn Error>> {
+		FileLock::exclusive(self)
+	}
+
+	/// Lock the file shared with other readers, waiting until exclusive locks are released. The lock is released when the returned guard is dropped.
+	pub fn lock_shared(&self) -> Result<FileLock, Box<dyn Error>> {
+		FileLock::shared(self)
+	}
+
+	/// Start a transaction on the file. All changes are made to a working copy, which only replaces the file when committed.
+	pub fn transaction(&self) -> Result<FileTransaction, Box<dyn Error>> {
+		FileTransaction::new(self)
+	}
+
+
+
 	/* FILE MOVING METHODS */
 
-	/// Move the file to another location.
+	/// Move the file or dir to another location. Files and dirs that can not be renamed because the target is on another filesystem are copied and then deleted.
 	pub fn move_to(&self, target:&FileRef) -> Result<(), Box<dyn Error>> {
-		use std::fs::rename;
+		use std::{ fs::rename, io::ErrorKind };
 
 		if self.is_dir() {
-			Err(format!("Could not copy dir \"{}\". Only able to copy files.", self.path()).into())
+			target.guarantee_parent_dir()?;
+			match rename(self.path(), target.path()) {
+				Err(error) if error.kind() == ErrorKind::CrossesDevices => {
+					self.copy_dir_to(target)?;
+					self.delete()
+				},
+				result => result.map_err(|error| error.into())
+			}
+		} else {
+			self.move_to_with_progress(target, |_, _| {})
+		}
+	}
+
+	/// Move the file to another location, calling the progress function with the amount of bytes moved so far and the total amount of bytes. When the target is on another filesystem, the file is copied in chunks and only deleted once the copy is complete, otherwise it is renamed and the progress is reported once.
+	pub fn move_to_with_progress(&self, target:&FileRef, mut on_progress:impl FnMut(u64, u64)) -> Result<(), Box<dyn Error>> {
+		use std::{ fs::rename, io::ErrorKind };
+
+		if self.is_dir() {
+			Err(format!("Could not move dir \"{}\" with progress. Only able to move files with progress.", self.path()).into())
 		} else if !self.exists() {
-			Err(format!("Could not copy file \"{}\". File does not exist.", self.path()).into())
+			Err(format!("Could not move file \"{}\". File does not exist.", self.path()).into())
 		} else {
 			target.guarantee_parent_dir()?;
-			rename(self.path(), target.path()).map_err(|error| error.into())
+			let total_bytes:u64 = self.bytes_size();
+			match rename(self.path(), target.path()) {
+				Err(error) if error.kind() == ErrorKind::CrossesDevices => self.move_by_copy(target, on_progress),
+				Err(error) => Err(error.into()),
+				Ok(_) => {
+					on_progress(total_bytes, total_bytes);
+					Ok(())
+				}
+			}
+		}
+	}
+
+	/// Move the file by copying it and deleting the source afterwards. The source is kept when the copy does not hold all of its bytes, in which case the incomplete copy is removed.
+	pub(crate) fn move_by_copy(&self, target:&FileRef, on_progress:impl FnMut(u64, u64)) -> Result<(), Box<dyn Error>> {
+		let total_bytes:u64 = self.bytes_size();
+		let bytes_copied:u64 = self.copy_to_with_progress(target, on_progress)?;
+		if bytes_copied != total_bytes || target.bytes_size() != total_bytes {
+			target.delete()?;
+			Err(format!("Could not move file \"{}\" to \"{}\". Only {bytes_copied} of {total_bytes} bytes were copied.", self.path(), target.path()).into())
+		} else {
+			self.delete()
+		}
+	}
+
+	/// Rename the file or dir, keeping it in the same dir. Returns the renamed file.
+	pub fn rename(&self, new_name:&str) -> Result<FileRef, Box<dyn Error>> {
+		if new_name.contains(SEPARATOR) || new_name.contains(INVALID_SEPARATOR) {
+			Err(format!("Could not rename \"{}\" to \"{new_name}\". The new name can not contain a path separator.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not rename \"{}\". Path does not exist.", self.path()).into())
+		} else {
+			let target:FileRef = self.sibling(new_name)?;
+			std::fs::rename(self.path(), target.path())?;
+			Ok(target)
+		}
+	}
+
+	/// Move all files from the sub-dirs of this dir directly into this dir. When a file with the same name is already there, the conflict policy decides whether the file is skipped, overwrites the existing file or is moved under a free name like 'name (1).txt'. Optionally removes the sub-dirs that are left empty afterwards. Returns the number of files moved.
+	pub fn flatten(&self, conflict:ConflictPolicy, remove_empty_dirs:bool) -> Result<usize, Box<dyn Error>> {
+		if !self.exists() || !self.is_dir() {
+			return Err(format!("Could not flatten dir \"{}\". Dir does not exist.", self.path()).into());
+		}
+
+		let dir:FileRef = self.clone().absolute().trim_end_matches(SEPARATOR);
+		let top_dir:FileRef = dir.clone();
+		let nested_files:Vec<FileRef> = dir.scanner().include_files().recurse().sorted().filter(move |file| file.parent_dir().is_ok_and(|parent_dir| parent_dir != top_dir)).collect();
+		let mut moved_count:usize = 0;
+		for file in nested_files {
+			let mut target:FileRef = dir.clone() + SEPARATOR + file.name();
+			if target.exists() {
+				match conflict {
+					ConflictPolicy::Skip => continue,
+					ConflictPolicy::Overwrite => {},
+					ConflictPolicy::Rename => target = target.free_name()
+				}
+			}
+			file.move_to(&target)?;
+			moved_count += 1;
+		}
+
+		if remove_empty_dirs {
+			for sub_dir in dir.scanner().include_dirs().recurse().post_order().collect::<Vec<FileRef>>() {
+				if sub_dir.entry_count()? == 0 {
+					std::fs::remove_dir(sub_dir.path())?;
+				}
+			}
+		}
+		Ok(moved_count)
+	}
+
+	/// Find the first path in the same dir that does not exist yet, by adding ' (1)', ' (2)' and so on after the file name.
+	fn free_name(&self) -> FileRef {
+		let extension:String = self.extension().map(|extension| ".".to_owned() + extension).unwrap_or_default();
+		let mut index:usize = 1;
+		loop {
+			let candidate:FileRef = self.with_name(&format!("{} ({index}){extension}", self.file_name_no_extension()));
+			if !candidate.exists() {
+				return candidate;
+			}
+			index += 1;
 		}
 	}
 
-	/// Copy the file to another location. Returns the number of bytes written.
+	/// Copy the file to another location. Dirs are copied recursively, including empty sub-dirs. Returns the number of bytes written.
 	pub fn copy_to(&self, target:&FileRef) -> Result<u64, Box<dyn Error>> {
 		use std::fs::copy;
 
-		if self.is_dir() {
+		if !self.exists() {
+			Err(format!("Could not copy \"{}\". Path does not exist.", self.path()).into())
+		} else if self.is_dir() {
+			self.copy_dir_to(target)
+		} else {
+			target.guarantee_parent_dir()?;
+			copy(self.path(), target.path()).map_err(|error| error.into())
+		}
+	}
+
+	/// Copy the file to another location in chunks, calling the progress function after each chunk with the amount of bytes copied so far and the total amount of bytes. Keeps the permissions of the file. Returns the number of bytes written.
+	pub fn copy_to_with_progress(&self, target:&FileRef, on_progress:impl FnMut(u64, u64)) -> Result<u64, Box<dyn Error>> {
+		self._copy_to_buffered(target, COPY_CHUNK_SIZE, on_progress)
+	}
+
+	/// Copy the file to another location through a buffer of the given size. Larger buffers can speed up copies to and from network shares. Keeps the permissions of the file. Returns the number of bytes written.
+	pub fn copy_to_buffered(&self, target:&FileRef, buffer_size:usize) -> Result<u64, Box<dyn Error>> {
+		self._copy_to_buffered(target, buffer_size, |_, _| {})
+	}
+
+	/// Copy the file to another location through a buffer of the given size, calling the progress function after each chunk.
+	fn _copy_to_buffered(&self, target:&FileRef, buffer_size:usize, mut on_progress:impl FnMut(u64, u64)) -> Result<u64, Box<dyn Error>> {
+		use std::{ fs::File, io::Write };
+
+		if buffer_size == 0 {
+			Err(format!("Could not copy file \"{}\". Buffer size can not be 0.", self.path()).into())
+		} else if self.is_dir() {
 			Err(format!("Could not copy dir \"{}\". Only able to copy files.", self.path()).into())
 		} else if !self.exists() {
 			Err(format!("Could not copy file \"{}\". File does not exist.", self.path()).into())
 		} else {
 			target.guarantee_parent_dir()?;
-			copy(self.path(), target.path()).map_err(|error| error.into())
+			let total_bytes:u64 = self.bytes_size();
+			let mut source_file:File = File::open(self.path())?;
+			let mut target_file:File = File::create(target.path())?;
+			let mut buffer:Vec<u8> = vec![0; buffer_size];
+			let mut bytes_copied:u64 = 0;
+			loop {
+				let bytes_read:usize = source_file.read(&mut buffer)?;
+				if bytes_read == 0 {
+					break;
+				}
+				target_file.write_all(&buffer[..bytes_read])?;
+				bytes_copied += bytes_read as u64;
+				on_progress(bytes_copied, total_bytes);
+			}
+			target_file.set_permissions(self.permissions()?)?;
+			Ok(bytes_copied)
+		}
+	}
+
+	/// Copy the file to another location, continuing a previous interrupted copy. If the target is shorter than the file and holds the same bytes as the start of the file, only the remainder is copied. Otherwise the file is copied completely. Returns the number of bytes written.
+	pub fn copy_to_resumable(&self, target:&FileRef) -> Result<u64, Box<dyn Error>> {
+		use std::{ fs::{ File, OpenOptions }, io::{ copy, SeekFrom } };
+
+		if self.is_dir() {
+			Err(format!("Could not copy dir \"{}\". Only able to copy files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not copy file \"{}\". File does not exist.", self.path()).into())
+		} else if !target.exists() || target.is_dir() || target.bytes_size() > self.bytes_size() || !self.starts_with_contents_of(target)? {
+			self.copy_to(target)
+		} else {
+			let mut source_file:File = File::open(self.path())?;
+			let mut target_file:File = OpenOptions::new().append(true).open(target.path())?;
+			source_file.seek(SeekFrom::Start(target.bytes_size()))?;
+			Ok(copy(&mut source_file, &mut target_file)?)
+		}
+	}
+
+	/// Check if the file starts with the exact contents of the other file, comparing them in chunks.
+	pub(crate) fn starts_with_contents_of(&self, other:&FileRef) -> Result<bool, Box<dyn Error>> {
+		use std::fs::File;
+
+		let mut file:File = File::open(self.path())?;
+		let mut other_file:File = File::open(other.path())?;
+		let mut buffer:[u8; 8192] = [0; 8192];
+		let mut other_buffer:[u8; 8192] = [0; 8192];
+		loop {
+			let bytes_read:usize = other_file.read(&mut other_buffer)?;
+			if bytes_read == 0 {
+				return Ok(true);
+			}
+			if file.read_exact(&mut buffer[..bytes_read]).is_err() || buffer[..bytes_read] != other_buffer[..bytes_read] {
+				return Ok(false);
+			}
+		}
+	}
+
+	/// Split the file into parts of the given size, written into the target dir as '<name>.part0', '<name>.part1' and so on. The last part holds the remaining bytes. Returns the created parts in order.
+	pub fn split_into(&self, part_size:u64, target_dir:&FileRef) -> Result<Vec<FileRef>, Box<dyn Error>> {
+		use std::{ fs::File, io::copy };
+
+		if part_size == 0 {
+			Err(format!("Could not split file \"{}\". Part size can not be 0.", self.path()).into())
+		} else if self.is_dir() {
+			Err(format!("Could not split dir \"{}\". Only able to split files.", self.path()).into())
+		} else if !self.exists() {
+			Err(format!("Could not split file \"{}\". File does not exist.", self.path()).into())
+		} else {
+			let mut source_file:File = File::open(self.path())?;
+			let part_count:u64 = self.bytes_size().div_ceil(part_size).max(1);
+			let mut parts:Vec<FileRef> = Vec::new();
+			for part_index in 0..part_count {
+				let part:FileRef = target_dir.clone() + &format!("/{}.part{part_index}", self.name());
+				part.guarantee_parent_dir()?;
+				copy(&mut (&mut source_file).take(part_size), &mut File::create(part.path())?)?;
+				parts.push(part);
+			}
+			Ok(parts)
+		}
+	}
+
+	/// Join the given parts into the target file, in the given order. Overwrites the target if it exists.
+	pub fn join_parts(parts:&[FileRef], target:&FileRef) -> Result<(), Box<dyn Error>> {
+		use std::{ fs::File, io::copy };
+
+		target.guarantee_parent_dir()?;
+		let mut target_file:File = File::create(target.path())?;
+		for part in parts {
+			if !part.exists() {
+				return Err(format!("Could not join part \"{}\" into \"{}\". Part does not exist.", part.path(), target.path()).into());
+			}
+			copy(&mut File::open(part.path())?, &mut target_file)?;
+		}
+		Ok(())
+	}
+
+	/// Copy the dir and all its contents to another location. Returns the total number of bytes written.
+	fn copy_dir_to(&self, target:&FileRef) -> Result<u64, Box<dyn Error>> {
+		use std::fs::{ copy, create_dir_all };
+
+		let source_dir:FileRef = self.clone().absolute().trim_end_matches(SEPARATOR);
+		let target_dir:FileRef = target.clone().absolute().trim_end_matches(SEPARATOR);
+		if target_dir.path().starts_with(&(source_dir.path().to_owned() + SEPARATOR)) {
+			return Err(format!("Could not copy dir \"{}\" to \"{}\". Target is inside the source dir.", self.path(), target.path()).into());
+		}
+
+		create_dir_all(target_dir.path())?;
+		let mut bytes_copied:u64 = 0;
+		for entry in FileScanner::new(&source_dir).include_files().include_dirs().recurse() {
+			let entry_target:FileRef = target_dir.clone() + &entry.path()[source_dir.path().len()..];
+			if entry.is_dir() {
+				create_dir_all(entry_target.path())?;
+			} else {
+				entry_target.guarantee_parent_dir()?;
+				bytes_copied += copy(entry.path(), entry_target.path())?;
+			}
 		}
+		Ok(bytes_copied)
 	}
 
 
@@ -590,10 +1894,65 @@ impl FileRef {
 	pub fn list_dirs_recurse(&self) -> Vec<FileRef> {
 		self.scanner().include_dirs().recurse().collect()
 	}
+
+	/// Count the files and dirs directly in this dir. Only counts the raw dir entries, without creating a `FileRef` for each one.
+	pub fn entry_count(&self) -> Result<usize, Box<dyn Error>> {
+		if !self.exists() || !self.is_dir() {
+			Err(format!("Could not count entries in \"{}\". Dir does not exist.", self.path()).into())
+		} else {
+			Ok(std::fs::read_dir(self.path())?.count())
+		}
+	}
+
+	/// Count the files and dirs in this dir and all of its sub-dirs.
+	pub fn entry_count_recursive(&self) -> Result<usize, Box<dyn Error>> {
+		if !self.exists() || !self.is_dir() {
+			Err(format!("Could not count entries in \"{}\". Dir does not exist.", self.path()).into())
+		} else {
+			Ok(self.scanner().include_files().include_dirs().recurse().count())
+		}
+	}
+
+	/// Find the entry directly in this dir whose name matches the given name ignoring case, if any.
+	pub fn name_exists_ignore_case(&self, name:&str) -> Result<Option<FileRef>, Box<dyn Error>> {
+		if !self.exists() || !self.is_dir() {
+			return Err(format!("Could not search entries in \"{}\". Dir does not exist.", self.path()).into());
+		}
+		let name:String = name.to_lowercase();
+		for entry in std::fs::read_dir(self.path())? {
+			let entry_name:String = entry?.file_name().to_string_lossy().to_string();
+			if entry_name.to_lowercase() == name {
+				return Ok(Some(self.clone() + "/" + &entry_name));
+			}
+		}
+		Ok(None)
+	}
 }
 impl PartialEq<FileRef> for FileRef {
 	fn eq(&self, other:&FileRef) -> bool {
-		self.path() == other.path() || self.clone().absolute().path() == other.clone().absolute().path()
+		self.comparable_path() == other.comparable_path() || (!self.compares_directly_with(other) && self.resolved_comparable_path() == other.resolved_comparable_path())
+	}
+}
+impl PartialOrd for FileRef {
+	fn partial_cmp(&self, other:&FileRef) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl Ord for FileRef {
+	fn cmp(&self, other:&FileRef) -> Ordering {
+		// Order by resolved path like eq, only resolving against the working dir when mixing path kinds.
+		if self.compares_directly_with(other) {
+			self.comparable_path().cmp(other.comparable_path())
+		} else {
+			self.resolved_comparable_path().cmp(&other.resolved_comparable_path())
+		}
+	}
+}
+impl Hash for FileRef {
+	fn hash<H:Hasher>(&self, state:&mut H) {
+		// Equal refs can differ in everything but the last node of their resolved path, so only hash that. For absolute and plain relative paths it is the last node as written.
+		let path:Cow<str> = if self.is_absolute_path() || self.is_plain_relative_path() { Cow::Borrowed(self.comparable_path()) } else { self.resolved_comparable_path() };
+		path.rsplit(SEPARATOR).next().hash(state);
 	}
 }
 impl Add<&str> for FileRef {
@@ -608,6 +1967,41 @@ impl AddAssign<&str> for FileRef {
 		*self = FileRef::new(&(self.path().to_owned() + rhs));
 	}
 }
+impl AsRef<Path> for FileRef {
+	fn as_ref(&self) -> &Path {
+		// Separators stay '/', which Windows accepts as well.
+		Path::new(self.path())
+	}
+}
+impl FromStr for FileRef {
+	type Err = Infallible;
+
+	fn from_str(path:&str) -> Result<FileRef, Self::Err> {
+		Ok(FileRef::new(path))
+	}
+}
+impl TryFrom<&Path> for FileRef {
+	type Error = Box<dyn Error>;
+
+	fn try_from(path:&Path) -> Result<FileRef, Self::Error> {
+		match path.to_str() {
+			Some(path) => Ok(FileRef::new(path)),
+			None => Err(format!("Could not convert path \"{}\". Path is not valid UTF-8.", path.display()).into())
+		}
+	}
+}
+#[cfg(feature="serde")]
+impl serde::Serialize for FileRef {
+	fn serialize<S:serde::Serializer>(&self, serializer:S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.path())
+	}
+}
+#[cfg(feature="serde")]
+impl<'de> serde::Deserialize<'de> for FileRef {
+	fn deserialize<D:serde::Deserializer<'de>>(deserializer:D) -> Result<FileRef, D::Error> {
+		Ok(FileRef::new(&String::deserialize(deserializer)?))
+	}
+}
 impl Display for FileRef {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
 		write!(f, "{}", self.path())
@@ -707,4 +2101,40 @@ impl_inherit_str!(ret_self trim_end_matches, (pat:&str));
 impl_inherit_str!(ret_self repeat, (n:usize));
 impl_inherit_str!(ret_self replace, (from:&str, to:&str));
 impl_inherit_str!(ret_self_opt strip_prefix, (prefix:&str));
-impl_inherit_str!(ret_self_opt strip_suffix, (suffix:&str));
\ No newline at end of file
+impl_inherit_str!(ret_self_opt strip_suffix, (suffix:&str));
+
+
+
+/* BINARY INTEGER METHODS */
+macro_rules! impl_binary_int {
+	($int_type:ty, $read_le:ident, $read_be:ident, $write_le:ident, $write_be:ident) => {
+		impl FileRef {
+
+			#[doc = concat!("Read a little-endian `", stringify!($int_type), "` at the given offset.")]
+			pub fn $read_le(&self, offset:u64) -> Result<$int_type, Box<dyn Error>> {
+				Ok(<$int_type>::from_le_bytes(self.read_exact_at(offset)?))
+			}
+
+			#[doc = concat!("Read a big-endian `", stringify!($int_type), "` at the given offset.")]
+			pub fn $read_be(&self, offset:u64) -> Result<$int_type, Box<dyn Error>> {
+				Ok(<$int_type>::from_be_bytes(self.read_exact_at(offset)?))
+			}
+
+			#[doc = concat!("Write a little-endian `", stringify!($int_type), "` at the given offset.")]
+			pub fn $write_le(&self, offset:u64, value:$int_type) -> Result<(), Box<dyn Error>> {
+				self.write_bytes_to_range(offset, &value.to_le_bytes())
+			}
+
+			#[doc = concat!("Write a big-endian `", stringify!($int_type), "` at the given offset.")]
+			pub fn $write_be(&self, offset:u64, value:$int_type) -> Result<(), Box<dyn Error>> {
+				self.write_bytes_to_range(offset, &value.to_be_bytes())
+			}
+		}
+	};
+}
+impl_binary_int!(u16, read_u16_le, read_u16_be, write_u16_le, write_u16_be);
+impl_binary_int!(u32, read_u32_le, read_u32_be, write_u32_le, write_u32_be);
+impl_binary_int!(u64, read_u64_le, read_u64_be, write_u64_le, write_u64_be);
+impl_binary_int!(i16, read_i16_le, read_i16_be, write_i16_le, write_i16_be);
+impl_binary_int!(i32, read_i32_le, read_i32_be, write_i32_le, write_i32_be);
+impl_binary_int!(i64, read_i64_le, read_i64_be, write_i64_le, write_i64_be);
\ No newline at end of file
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
-	use std::{ thread::sleep, time::{ Duration, SystemTime } };
-	use crate::{ FileRef, unit_test_support::TempFile };
+	use std::{ borrow::Cow, path::PathBuf, thread::sleep, time::{ Duration, SystemTime } };
+	use crate::{ ConflictPolicy, FileErrors, FileRef, LineEnding, unit_test_support::{ Syscall, TempFile, count_syscalls } };
 
 	
 
@@ -15,6 +15,18 @@ mod tests {
 		assert_eq!(fs_path.path(), "dir/file.txt");
 	}
 
+	#[test]
+	fn test_new_validated() {
+		assert_eq!(FileRef::new_validated("dir/file.txt").unwrap(), FileRef::new("dir/file.txt"));
+		assert!(FileRef::new_validated("dir/fi\0le.txt").is_err());
+
+		let windows_illegal_paths:[&str; 4] = ["dir/file?.txt", "dir/<file>.txt", "dir/file:stream.txt", "dir/file\u{1}.txt"];
+		for path in windows_illegal_paths {
+			assert_eq!(FileRef::new_validated(path).is_err(), cfg!(windows));
+		}
+		assert!(FileRef::new_validated("C:/dir/file.txt").is_ok());
+	}
+
 	#[test]
 	fn test_new_const() {
 		const PATH:&str = "static/dir/file.txt";
@@ -42,6 +54,17 @@ mod tests {
 		assert!(fs_path.path().contains(":"), "Did not correctly create absolute path");
 	}
 
+	#[test]
+	fn test_is_absolute_path() {
+		assert!(FileRef::new("/home/user/file.txt").is_absolute_path());
+		assert!(FileRef::new("/").is_absolute_path());
+		assert!(FileRef::new("C:/Users/file.txt").is_absolute_path());
+		assert!(FileRef::new("dir/file.txt").is_relative_path());
+		assert!(FileRef::new("file.txt").is_relative_path());
+		assert!(FileRef::new("").is_relative_path());
+		assert_eq!(FileRef::new("/home/user").absolute().path(), "/home/user");
+	}
+
 	#[test]
 	fn test_path_to_relative() {
 		let path:String = std::env::current_dir().unwrap().display().to_string() + "/dir/file.txt";
@@ -56,6 +79,111 @@ mod tests {
 		assert_eq!(path.relative_path_to(&fs_path).path(), "../../Download/cracked_version_of_free_tool/definitely_not_a_virus.exe");
 	}
 
+	#[test]
+	fn test_relative_path_to_dir() {
+		let path:FileRef = FileRef::new("C:/users/Me/Desktop/");
+		let expected:&str = "../Download/sibling_dir";
+		assert_eq!(path.relative_path_to(&FileRef::new("C:/users/Me/Download/sibling_dir")).path(), expected);
+		assert_eq!(path.relative_path_to(&FileRef::new("C:/users/Me/Download/sibling_dir/")).path(), expected);
+		assert_eq!(path.relative_path_to(&FileRef::new_const("C:/users/Me/Download/sibling_dir/")).path(), expected);
+		assert_eq!(FileRef::new("C:/users/Me/Desktop").relative_path_to(&FileRef::new("C:/users/Me/Download/sibling_dir/")).path(), expected);
+	}
+
+	#[test]
+	fn test_relative_path_to_from() {
+		let base_dir:FileRef = FileRef::new("/projects/app");
+		assert_eq!(FileRef::new("src").relative_path_to_from(&FileRef::new("assets/logo.png"), &base_dir).path(), "../assets/logo.png");
+		assert_eq!(FileRef::new("src").relative_path_to_from(&FileRef::new("/projects/lib/mod.rs"), &base_dir).path(), "../../lib/mod.rs");
+		assert_eq!(FileRef::new("/projects/lib").relative_path_to_from(&FileRef::new("src/main.rs"), &base_dir).path(), "../app/src/main.rs");
+		assert_eq!(FileRef::new("/other").relative_path_to_from(&FileRef::new("/projects"), &base_dir).path(), "../projects");
+	}
+
+	#[test]
+	#[cfg(feature="serde")]
+	fn test_serde_round_trip() {
+		let path:FileRef = FileRef::new("dir\\sub_dir/file.txt");
+		let json:String = serde_json::to_string(&path).unwrap();
+		assert_eq!(json, "\"dir/sub_dir/file.txt\"");
+		assert_eq!(serde_json::from_str::<FileRef>(&json).unwrap(), path);
+		assert_eq!(serde_json::from_str::<Vec<FileRef>>("[\"a.txt\", \"b\\\\c.txt\"]").unwrap(), vec![FileRef::new("a.txt"), FileRef::new("b/c.txt")]);
+		assert!(serde_json::from_str::<FileRef>("42").is_err());
+	}
+
+	#[test]
+	fn test_sibling() {
+		assert_eq!(FileRef::new("dir/sub_dir/file.txt").sibling("file.txt.bak").unwrap().path(), "dir/sub_dir/file.txt.bak");
+		assert_eq!(FileRef::new("/dir/file.txt").sibling("other").unwrap().path(), "/dir/other");
+		assert!(FileRef::new("dir/file.txt").sibling("sub_dir/file.txt").is_err());
+		assert!(FileRef::new("dir/file.txt").sibling("sub_dir\\file.txt").is_err());
+	}
+
+	#[test]
+	fn test_with_extension() {
+		assert_eq!(FileRef::new("docs/foo.md").with_extension("html").path(), "docs/foo.html");
+		assert_eq!(FileRef::new("docs/archive.tar.gz").with_extension("zip").path(), "docs/archive.tar.zip");
+		assert_eq!(FileRef::new("docs/README").with_extension("txt").path(), "docs/README.txt");
+		assert_eq!(FileRef::new("docs/foo.md").with_extension("").path(), "docs/foo");
+		assert_eq!(FileRef::new("foo.md").with_extension("rs").path(), "foo.rs");
+	}
+
+	#[test]
+	fn test_with_timestamp() {
+		let time:SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(1717245000); // 2024-06-01 12:30:00 UTC.
+		assert_eq!(FileRef::new("out/report.txt").with_timestamp(time, "").path(), "out/report-2024-06-01T12-30-00.txt");
+		assert_eq!(FileRef::new("out/archive.tar.gz").with_timestamp(time, "%Y%m%d").path(), "out/archive.tar-20240601.gz");
+		assert_eq!(FileRef::new("report").with_timestamp(time, "%H%M%S").path(), "report-123000");
+		assert_eq!(FileRef::new("leap.log").with_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(951782400), "%Y-%m-%d").path(), "leap-2000-02-29.log");
+	}
+
+	#[test]
+	fn test_to_native_separators() {
+		let fs_path:FileRef = FileRef::new("../scripts/build/run.bat");
+		let expected:&str = if cfg!(windows) { "..\\scripts\\build\\run.bat" } else { "../scripts/build/run.bat" };
+		assert_eq!(fs_path.to_native_separators(), expected);
+		assert_eq!(fs_path.path(), "../scripts/build/run.bat");
+	}
+
+	#[test]
+	fn test_path_buf_round_trip() {
+		let fs_path:FileRef = FileRef::new("dir/subdir/file.txt");
+		let path_buf:PathBuf = fs_path.to_path_buf();
+		assert_eq!(path_buf, PathBuf::from("dir/subdir/file.txt"));
+		assert_eq!(FileRef::new(path_buf.to_str().unwrap()), fs_path);
+
+		let path_buf:PathBuf = fs_path.clone().into_path_buf();
+		assert_eq!(FileRef::new(path_buf.to_str().unwrap()), fs_path);
+	}
+
+	#[test]
+	fn test_as_ref_path() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let file:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/file.txt";
+		file.write("content".to_string()).unwrap();
+
+		assert_eq!(std::fs::read_to_string(&file).unwrap(), "content");
+		assert!(std::fs::metadata(file.parent_dir().unwrap()).unwrap().is_dir());
+		let path:&std::path::Path = file.as_ref();
+		assert_eq!(path.file_name().unwrap(), "file.txt");
+	}
+
+	#[test]
+	fn test_from_str_and_path() {
+		let fs_path:FileRef = "dir\\subdir/file.txt".parse().unwrap();
+		assert_eq!(fs_path.path(), "dir/subdir/file.txt");
+
+		let fs_path:FileRef = FileRef::try_from(std::path::Path::new("dir/subdir/file.txt")).unwrap();
+		assert_eq!(fs_path.path(), "dir/subdir/file.txt");
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_try_from_non_utf8_path() {
+		use std::{ ffi::OsStr, os::unix::ffi::OsStrExt, path::Path };
+
+		let path:&Path = Path::new(OsStr::from_bytes(b"dir/\xFF.txt"));
+		assert!(FileRef::try_from(path).is_err());
+	}
+
 	#[test]
 	fn test_parent_dir() {
 		let fs_path:FileRef = FileRef::new("dir/subdir/file.txt");
@@ -82,6 +210,81 @@ mod tests {
 		assert_eq!(fs_path.parent_dir().unwrap().path(), "test1");
 	}
 
+	#[test]
+	fn test_real_case() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/Sub_Dir/Foo.txt";
+		temp_file_ref.create().unwrap();
+
+		assert_eq!(temp_file_ref.real_case().unwrap().path(), temp_file_ref.path());
+		assert_eq!(temp_file_ref.clone().absolute().real_case().unwrap().path(), temp_file_ref.clone().absolute().path());
+		assert!((FileRef::new(temp_dir.path()) + "/missing.txt").real_case().is_err());
+	}
+
+	#[test]
+	#[cfg(any(windows, target_os="macos"))]
+	fn test_real_case_insensitive() {
+		let temp_dir:TempFile = TempFile::new(None);
+		(FileRef::new(temp_dir.path()) + "/Foo.txt").create().unwrap();
+		assert_eq!((FileRef::new(temp_dir.path()) + "/foo.txt").real_case().unwrap().name(), "Foo.txt");
+	}
+
+	#[test]
+	fn test_canonicalize() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let file:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/file.txt";
+		file.write("content".to_string()).unwrap();
+
+		let messy_file:FileRef = FileRef::new(&format!("{}/sub_dir/../sub_dir/file.txt", temp_dir.path()));
+		let canonical_file:FileRef = messy_file.canonicalize().unwrap();
+		assert!(canonical_file.is_absolute_path());
+		assert!(!canonical_file.contains("\\"));
+		assert!(!canonical_file.contains(".."));
+		assert_eq!(canonical_file, file.canonicalize().unwrap());
+		assert_eq!(canonical_file.read().unwrap(), "content");
+		assert!((FileRef::new(temp_dir.path()) + "/missing.txt").canonicalize().is_err());
+	}
+
+	#[test]
+	fn test_trailing_separator_equality() {
+		use std::collections::HashSet;
+
+		assert_eq!(FileRef::new("a/b"), FileRef::new("a/b/"));
+		assert_eq!(FileRef::new_const("C:/a/b/"), FileRef::new("C:/a/b"));
+		assert_ne!(FileRef::new("a/b"), FileRef::new("a/b/c"));
+
+		let mut set:HashSet<FileRef> = HashSet::new();
+		set.insert(FileRef::new("a/b/"));
+		assert!(set.contains(&FileRef::new("a/b")));
+		assert!(!set.insert(FileRef::new("a/b")));
+
+		// Roots keep their separator.
+		assert_eq!(FileRef::new("/").path(), "/");
+		assert_eq!(FileRef::new("/").comparable_path(), "/");
+		assert_eq!(FileRef::new("C:/").comparable_path(), "C:/");
+		assert_ne!(FileRef::new("/"), FileRef::new(""));
+	}
+
+	#[test]
+	fn test_relative_absolute_equality() {
+		use std::collections::{ BTreeSet, HashSet };
+
+		let relative:FileRef = FileRef::new("target/some_file.txt");
+		let absolute:FileRef = relative.clone().absolute();
+		let working_dir_name:String = FileRef::working_dir().name().to_string();
+		let stepping_out:FileRef = FileRef::new(&format!("../{working_dir_name}/target/some_file.txt"));
+		assert_eq!(relative, absolute);
+		assert_eq!(stepping_out, relative);
+		assert_eq!(stepping_out, absolute);
+
+		let hash_set:HashSet<FileRef> = [relative.clone(), absolute.clone(), stepping_out.clone()].into_iter().collect();
+		assert_eq!(hash_set.len(), 1);
+		let tree_set:BTreeSet<FileRef> = [relative.clone(), absolute.clone(), stepping_out, FileRef::new("target/a.txt")].into_iter().collect();
+		assert_eq!(tree_set.len(), 2);
+		assert!(FileRef::new("target/a.txt") < absolute);
+		assert!(FileRef::new("target/z.txt") > absolute);
+	}
+
 	#[test]
 	fn test_path_nodes() {
 		let fs_path:FileRef = FileRef::new("dir/subdir/file.txt");
@@ -196,6 +399,89 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_within_size() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let root:FileRef = FileRef::new(temp_dir.path());
+		let file:FileRef = root.clone() + "/file.bin";
+		file.write_bytes(&[0; 100]).unwrap();
+		assert!(file.within_size(100).unwrap());
+		assert!(!file.within_size(99).unwrap());
+		assert!((root.clone() + "/missing.bin").within_size(100).is_err());
+
+		for index in 0..50 {
+			(root.clone() + &format!("/sub_dir/file{index}.bin")).write_bytes(&[0; 100]).unwrap();
+		}
+		assert!(root.within_size(5100).unwrap());
+		let stat_count:usize = count_syscalls(Syscall::Stat, || assert!(!root.within_size(150).unwrap()));
+		assert!(stat_count < 20); // Stops after a few files instead of sizing all 51.
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_is_symlink() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let file:FileRef = FileRef::new(temp_dir.path()) + "/file.txt";
+		let link:FileRef = FileRef::new(temp_dir.path()) + "/link.txt";
+		let broken_link:FileRef = FileRef::new(temp_dir.path()) + "/broken_link.txt";
+		file.write("content".to_string()).unwrap();
+		std::os::unix::fs::symlink(file.path(), link.path()).unwrap();
+		std::os::unix::fs::symlink((FileRef::new(temp_dir.path()) + "/missing.txt").path(), broken_link.path()).unwrap();
+
+		assert!(link.is_symlink());
+		assert!(broken_link.is_symlink());
+		assert!(!file.is_symlink());
+		assert!(!FileRef::new(temp_dir.path()).is_symlink());
+	}
+
+	#[test]
+	#[cfg(feature="disk_space")]
+	fn test_disk_space() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_dir_ref:FileRef = FileRef::new(temp_dir.path());
+		temp_dir_ref.create().unwrap();
+
+		let available:u64 = temp_dir_ref.available_space().unwrap();
+		let total:u64 = temp_dir_ref.total_space().unwrap();
+		assert!(available > 0);
+		assert!(total >= available);
+		assert!((temp_dir_ref.clone() + "/missing_dir").available_space().is_err());
+	}
+
+	#[test]
+	fn test_wait_until_size() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+
+		let writer_file_ref:FileRef = temp_file_ref.clone();
+		let writer = std::thread::spawn(move || {
+			for _ in 0..5 {
+				sleep(Duration::from_millis(20));
+				writer_file_ref.append_bytes_create(&[0; 100]).unwrap();
+			}
+		});
+		assert!(temp_file_ref.wait_until_size(500, Duration::from_secs(5), Duration::from_millis(5)).unwrap());
+		writer.join().unwrap();
+
+		assert!(!temp_file_ref.wait_until_size(501, Duration::from_millis(50), Duration::from_millis(10)).unwrap());
+	}
+
+	#[test]
+	fn test_set_times() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		assert!(temp_file_ref.set_time_modification(SystemTime::now()).is_err());
+		temp_file_ref.write("content".to_string()).unwrap();
+
+		let time_modify:SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+		let time_access:SystemTime = SystemTime::UNIX_EPOCH + Duration::from_secs(1_100_000_000);
+		temp_file_ref.set_time_modification(time_modify).unwrap();
+		temp_file_ref.set_time_accessed(time_access).unwrap();
+		assert_eq!(temp_file_ref.get_time_modification().unwrap(), time_modify);
+		assert_eq!(temp_file_ref.get_time_accessed().unwrap(), time_access);
+		assert_eq!(temp_file_ref.read().unwrap(), "content");
+	}
+
 	#[test]
 	fn test_dates() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -250,6 +536,132 @@ mod tests {
 		assert!(temp_file_ref.exists());
 	}
 
+	#[test]
+	fn test_touch() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+
+		temp_file_ref.touch().unwrap();
+		assert!(temp_file_ref.exists());
+		assert_eq!(temp_file_ref.bytes_size(), 0);
+
+		temp_file_ref.write("content".to_string()).unwrap();
+		temp_file_ref.set_time_modification(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000)).unwrap();
+		let time_before:SystemTime = temp_file_ref.get_time_modification().unwrap();
+
+		temp_file_ref.touch().unwrap();
+		assert!(temp_file_ref.get_time_modification().unwrap() > time_before);
+		assert_eq!(temp_file_ref.read().unwrap(), "content");
+	}
+
+	#[test]
+	fn test_create_new_with() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let lock_file:FileRef = FileRef::new(temp_dir.path()) + "/locks/app.lock";
+
+		lock_file.create_new_with(b"pid=1234").unwrap();
+		assert!(lock_file.create_new_with(b"pid=5678").is_err());
+		assert_eq!(lock_file.read().unwrap(), "pid=1234");
+	}
+
+	#[test]
+	fn test_create_all() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let nested_dir:FileRef = FileRef::new(temp_dir.path()) + "/a/b/c.d";
+		nested_dir.create_all().unwrap();
+		assert!(std::fs::metadata(nested_dir.path()).unwrap().is_dir());
+		nested_dir.create_all().unwrap();
+
+		let file:FileRef = FileRef::new(temp_dir.path()) + "/a/file.txt";
+		file.create().unwrap();
+		assert!(file.create_all().unwrap_err().to_string().contains(file.path()));
+		assert!((file.clone() + "/sub_dir").create_all().unwrap_err().to_string().contains(file.path()));
+	}
+
+	#[test]
+	fn test_as_file_and_as_dir() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let readme:FileRef = FileRef::new(temp_dir.path()) + "/README";
+		assert!(readme.is_dir());
+		let readme:FileRef = readme.as_file();
+		assert!(readme.is_file());
+		readme.create().unwrap();
+		assert!(std::fs::metadata(readme.path()).unwrap().is_file());
+
+		let versioned_dir:FileRef = (FileRef::new(temp_dir.path()) + "/data.v2").as_dir();
+		assert!(versioned_dir.is_dir());
+		versioned_dir.create().unwrap();
+		assert!(std::fs::metadata(versioned_dir.path()).unwrap().is_dir());
+
+		// Existing paths are classified by their metadata, not by the mark.
+		assert!(readme.clone().as_dir().is_file());
+		assert_eq!(readme.clone().as_dir(), readme);
+
+		// The mark does not take part in ordering either.
+		let marked:FileRef = (FileRef::new(temp_dir.path()) + "/notes").as_file();
+		let unmarked:FileRef = FileRef::new(temp_dir.path()) + "/notes";
+		assert_eq!(marked.cmp(&unmarked), std::cmp::Ordering::Equal);
+		let set:std::collections::BTreeSet<FileRef> = [marked, unmarked].into_iter().collect();
+		assert_eq!(set.len(), 1);
+	}
+
+	#[test]
+	fn test_stat_count() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/file.txt";
+		FileRef::new(temp_dir.path()).create().unwrap();
+		assert_eq!(count_syscalls(Syscall::Stat, || temp_file_ref.create().unwrap()), 3); // Own type, own existence and parent dir existence.
+		assert_eq!(count_syscalls(Syscall::Stat, || { temp_file_ref.get_time_modification().unwrap(); }), 1);
+	}
+
+	#[test]
+	fn test_guarantee_parent_dirs() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let files:Vec<FileRef> = (0..20).map(|index| FileRef::new(&format!("{}/dir{}/sub_dir{}/file{index}.txt", temp_dir.path(), index % 3, index % 2))).collect();
+
+		FileRef::guarantee_parent_dirs(&files).unwrap();
+		for file in &files {
+			assert!(file.parent_dir().unwrap().exists());
+			file.create().unwrap();
+			assert!(file.exists());
+		}
+		assert_eq!(FileRef::new(temp_dir.path()).list_files_recurse().len(), files.len());
+	}
+
+	#[test]
+	fn test_with_parent_dir() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let file:FileRef = FileRef::new(temp_dir.path()) + "/a/b/c/file.txt";
+
+		let size:u64 = file.with_parent_dir(|file| {
+			std::fs::write(file.path(), "content")?;
+			Ok(file.bytes_size())
+		}).unwrap();
+		assert_eq!(size, 7);
+		assert_eq!(file.read().unwrap(), "content");
+	}
+
+	#[test]
+	#[cfg(all(unix, feature="fifo"))]
+	fn test_create_fifo() {
+		let temp_file:TempFile = TempFile::new(Some("fifo"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+
+		temp_file_ref.create_fifo(0o644).unwrap();
+		assert!(temp_file_ref.exists());
+		assert!(temp_file_ref.is_fifo());
+		assert!(temp_file_ref.create_fifo(0o644).is_err());
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_regular_file_is_not_fifo() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.create().unwrap();
+		assert!(!temp_file_ref.is_fifo());
+	}
+
 	#[test]
 	fn test_file_write_and_read() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -264,6 +676,125 @@ mod tests {
 		assert_eq!(content, read_content);
 	}
 
+	#[test]
+	fn test_read_lines() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("line 1\nline 2\r\n\nline 4".to_string()).unwrap();
+
+		let lines:Vec<String> = temp_file_ref.read_lines().unwrap().map(|line| line.unwrap()).collect();
+		assert_eq!(lines, vec!["line 1", "line 2", "", "line 4"]);
+
+		assert!(FileRef::new(&(temp_file.path().to_owned() + ".missing.txt")).read_lines().is_err());
+	}
+
+	#[test]
+	fn test_read_lossy() {
+		let temp_file:TempFile = TempFile::new(Some("log"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write_bytes(b"line 1\nbad \xFF byte\n").unwrap();
+
+		assert!(temp_file_ref.read().is_err());
+		assert_eq!(temp_file_ref.read_lossy().unwrap(), "line 1\nbad \u{FFFD} byte\n");
+		assert!(FileRef::new(&(temp_file.path().to_owned() + ".missing.log")).read_lossy().is_err());
+	}
+
+	#[test]
+	#[cfg(feature="encoding")]
+	fn test_read_with_encoding() {
+		use crate::Encoding;
+
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let text:&str = "Grüße 😀";
+
+		let mut utf16_le:Vec<u8> = vec![0xFF, 0xFE];
+		utf16_le.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+		temp_file_ref.write_bytes(&utf16_le).unwrap();
+		assert_eq!(temp_file_ref.read_with_encoding(Encoding::Utf8).unwrap(), text);
+		assert_eq!(temp_file_ref.read_with_encoding(Encoding::Utf16Be).unwrap(), text);
+
+		let utf16_be:Vec<u8> = text.encode_utf16().flat_map(|unit| unit.to_be_bytes()).collect();
+		temp_file_ref.write_bytes(&utf16_be).unwrap();
+		assert_eq!(temp_file_ref.read_with_encoding(Encoding::Utf16Be).unwrap(), text);
+		temp_file_ref.write_bytes(&utf16_be[1..]).unwrap();
+		assert!(temp_file_ref.read_with_encoding(Encoding::Utf16Be).is_err());
+
+		temp_file_ref.write_bytes(&[0xEF, 0xBB, 0xBF, b'a', b'b']).unwrap();
+		assert_eq!(temp_file_ref.read_with_encoding(Encoding::Utf8).unwrap(), "ab");
+
+		temp_file_ref.write_bytes(&[b'G', 0xFC, b'r', 0xDF]).unwrap();
+		assert_eq!(temp_file_ref.read_with_encoding(Encoding::Latin1).unwrap(), "Gür\u{DF}");
+		assert!(temp_file_ref.read_with_encoding(Encoding::Utf8).is_err());
+	}
+
+	#[test]
+	fn test_read_cow() {
+		const DEFAULTS:FileRef = FileRef::new_embedded("config/defaults.txt", "retries=3\n");
+		assert!(matches!(DEFAULTS.read_cow().unwrap(), Cow::Borrowed("retries=3\n")));
+		assert_eq!(DEFAULTS.path(), "config/defaults.txt");
+
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("retries=5\n".to_string()).unwrap();
+		assert!(matches!(temp_file_ref.read_cow().unwrap(), Cow::Owned(contents) if contents == "retries=5\n"));
+		assert!(FileRef::new_const("config/missing.txt").read_cow().is_err());
+	}
+
+	#[test]
+	fn test_read_first_lines() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let mut data:Vec<u8> = b"line 1\nline 2\r\nline 3\n".to_vec();
+		data.extend_from_slice(&[0xFF, 0xFE, b'\n']); // Invalid UTF-8, only fails when read.
+		temp_file_ref.write_bytes(&data).unwrap();
+
+		assert_eq!(temp_file_ref.read_first_lines(2).unwrap(), vec!["line 1", "line 2"]);
+		assert_eq!(temp_file_ref.read_first_lines(3).unwrap(), vec!["line 1", "line 2", "line 3"]);
+		assert!(temp_file_ref.read_first_lines(4).is_err());
+		assert!(temp_file_ref.read_first_lines(0).unwrap().is_empty());
+
+		temp_file_ref.write("short".to_string()).unwrap();
+		assert_eq!(temp_file_ref.read_first_lines(10).unwrap(), vec!["short"]);
+	}
+
+	#[test]
+	fn test_line_endings() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+
+		temp_file_ref.write("line 1\r\nline 2\rline 3\n".to_string()).unwrap();
+		assert_eq!(temp_file_ref.read_normalized_newlines().unwrap(), "line 1\nline 2\nline 3\n");
+
+		temp_file_ref.write_with_line_ending("line 1\nline 2\r\nline 3\n", LineEnding::CrLf).unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "line 1\r\nline 2\r\nline 3\r\n");
+		assert_eq!(temp_file_ref.read_normalized_newlines().unwrap(), "line 1\nline 2\nline 3\n");
+
+		temp_file_ref.write_with_line_ending("line 1\r\nline 2\r\n", LineEnding::Lf).unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "line 1\nline 2\n");
+	}
+
+	#[test]
+	#[cfg(feature="flate2")]
+	fn test_read_auto() {
+		use std::io::Write;
+		use flate2::{ write::GzEncoder, Compression };
+
+		let content:&str = "Hello, compressed world!";
+		let plain_file:TempFile = TempFile::new(Some("txt"));
+		let plain_file_ref:FileRef = FileRef::new(plain_file.path());
+		plain_file_ref.write(content.to_string()).unwrap();
+
+		let gz_file:TempFile = TempFile::new(Some("txt.gz"));
+		let gz_file_ref:FileRef = FileRef::new(gz_file.path());
+		let mut encoder:GzEncoder<Vec<u8>> = GzEncoder::new(Vec::new(), Compression::default());
+		encoder.write_all(content.as_bytes()).unwrap();
+		gz_file_ref.write_bytes(&encoder.finish().unwrap()).unwrap();
+
+		assert_eq!(plain_file_ref.read_auto().unwrap(), content);
+		assert_eq!(gz_file_ref.read_auto().unwrap(), content);
+	}
+
 	#[test]
 	fn test_file_write_bytes_and_read_bytes() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -278,6 +809,83 @@ mod tests {
 		assert_eq!(content, read_content.as_slice());
 	}
 
+	#[test]
+	#[cfg(unix)]
+	fn test_write_bytes_preserving_mode() {
+		use std::{ fs::Permissions, os::unix::fs::PermissionsExt };
+
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write_bytes_preserving_mode(b"first").unwrap();
+		std::fs::set_permissions(temp_file_ref.path(), Permissions::from_mode(0o600)).unwrap();
+
+		temp_file_ref.write_bytes_preserving_mode(b"second").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "second");
+		assert_eq!(temp_file_ref.permissions().unwrap().mode() & 0o777, 0o600);
+	}
+
+	#[test]
+	fn test_write_if_newer() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let old_time:SystemTime = SystemTime::now() - Duration::from_secs(3600);
+		let new_time:SystemTime = SystemTime::now() - Duration::from_secs(60);
+
+		// Missing destination.
+		assert!(temp_file_ref.write_if_newer(b"old", old_time).unwrap());
+		assert_eq!(temp_file_ref.read().unwrap(), "old");
+		assert_eq!(temp_file_ref.get_time_modification().unwrap(), old_time);
+
+		// Older destination.
+		assert!(temp_file_ref.write_if_newer(b"new", new_time).unwrap());
+		assert_eq!(temp_file_ref.read().unwrap(), "new");
+		assert_eq!(temp_file_ref.get_time_modification().unwrap(), new_time);
+
+		// Newer destination.
+		assert!(!temp_file_ref.write_if_newer(b"old", old_time).unwrap());
+		assert_eq!(temp_file_ref.read().unwrap(), "new");
+		assert_eq!(temp_file_ref.get_time_modification().unwrap(), new_time);
+	}
+
+	#[test]
+	fn test_is_newer_than() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let old_file:FileRef = FileRef::new(temp_dir.path()) + "/old.txt";
+		let new_file:FileRef = FileRef::new(temp_dir.path()) + "/new.txt";
+		let missing_file:FileRef = FileRef::new(temp_dir.path()) + "/missing.txt";
+		old_file.write_if_newer(b"old", SystemTime::now() - Duration::from_secs(3600)).unwrap();
+		new_file.write_if_newer(b"new", SystemTime::now() - Duration::from_secs(60)).unwrap();
+
+		assert!(new_file.is_newer_than(&old_file).unwrap());
+		assert!(!old_file.is_newer_than(&new_file).unwrap());
+		assert!(!new_file.is_newer_than(&new_file).unwrap());
+		assert!(old_file.is_older_than(&new_file).unwrap());
+		assert!(!new_file.is_older_than(&old_file).unwrap());
+		assert!(old_file.is_newer_than(&missing_file).unwrap());
+		assert!(!old_file.is_older_than(&missing_file).unwrap());
+		assert!(missing_file.is_newer_than(&old_file).is_err());
+	}
+
+	#[test]
+	fn test_write_to_all() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_dir_ref:FileRef = FileRef::new(temp_dir.path());
+		temp_dir_ref.create().unwrap();
+		let targets:Vec<FileRef> = vec![temp_dir_ref.clone() + "/a.txt", temp_dir_ref.clone() + "/b.txt", temp_dir_ref.clone() + "/new_dir/c.txt"];
+
+		FileRef::write_to_all(&targets, b"mirrored").unwrap();
+		for target in &targets {
+			assert_eq!(target.read().unwrap(), "mirrored");
+		}
+
+		// Failing targets are reported without stopping the others.
+		let targets:Vec<FileRef> = vec![temp_dir_ref.clone() + "/new_dir", temp_dir_ref.clone() + "/a.txt"];
+		let errors:FileErrors = FileRef::write_to_all(&targets, b"updated").unwrap_err();
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].0, targets[0]);
+		assert_eq!(targets[1].read().unwrap(), "updated");
+	}
+
 	#[test]
 	fn test_append_bytes() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -294,6 +902,67 @@ mod tests {
 		assert_eq!(read_content, "Hello, world!");
 	}
 
+	#[test]
+	fn test_append_bytes_synced() {
+		let temp_file:TempFile = TempFile::new(Some("log"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		assert!(temp_file_ref.append_bytes_synced(b"entry 1\n").is_err());
+
+		temp_file_ref.create().unwrap();
+		temp_file_ref.append_bytes_synced(b"entry 1\n").unwrap();
+		temp_file_ref.append_bytes_synced(b"entry 2\n").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "entry 1\nentry 2\n");
+	}
+
+	#[test]
+	fn test_append_bytes_create() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/log.txt";
+
+		// Nonexistent file.
+		temp_file_ref.append_bytes_create(b"Hello").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Hello");
+
+		// Existing file.
+		temp_file_ref.append_bytes_create(b", world!").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Hello, world!");
+	}
+
+	#[test]
+	fn test_append_atomic() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/log.txt";
+
+		let writers:Vec<std::thread::JoinHandle<()>> = (0..8).map(|thread_index| {
+			let file:FileRef = temp_file_ref.clone();
+			std::thread::spawn(move || {
+				for line_index in 0..100 {
+					let marker:String = format!("{}\n", format!("{thread_index}-{line_index}|").repeat(20));
+					file.append_atomic(marker.as_bytes()).unwrap();
+				}
+			})
+		}).collect();
+		writers.into_iter().for_each(|writer| writer.join().unwrap());
+
+		let lines:Vec<String> = temp_file_ref.read_lines().unwrap().map(|line| line.unwrap()).collect();
+		assert_eq!(lines.len(), 800);
+		for line in lines {
+			let marker:&str = line.split('|').next().unwrap();
+			assert_eq!(line, format!("{marker}|").repeat(20));
+		}
+	}
+
+	#[test]
+	fn test_append_line() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/logs/app.log";
+
+		temp_file_ref.append_line("first").unwrap();
+		temp_file_ref.append_line_await("second").unwrap();
+		temp_file_ref.append_line_with_ending("third", LineEnding::CrLf).unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "first\nsecond\nthird\r\n");
+	}
+
 	#[test]
 	fn test_read_range() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -308,6 +977,272 @@ mod tests {
 		assert_eq!(std::str::from_utf8(&range_content).unwrap(), "world");
 	}
 
+	#[test]
+	fn test_read_range_str() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("abc€def".to_string()).unwrap(); // '€' takes bytes 3 to 5.
+
+		assert_eq!(temp_file_ref.read_range_str(0, 6).unwrap(), "abc€");
+		assert_eq!(temp_file_ref.read_range_str(6, 9).unwrap(), "def");
+		assert!(temp_file_ref.read_range_str(0, 5).unwrap_err().to_string().contains("End 5 is in the middle of a multi-byte character."));
+		assert!(temp_file_ref.read_range_str(4, 9).unwrap_err().to_string().contains("Start 4 is in the middle of a multi-byte character."));
+	}
+
+	#[test]
+	fn test_read_range_to_end() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Hello, world!".to_string()).unwrap();
+
+		assert_eq!(temp_file_ref.read_range(7, u64::MAX).unwrap(), b"world!");
+		assert_eq!(temp_file_ref.read_range(7, u64::MAX).unwrap(), temp_file_ref.read_from(7).unwrap());
+		assert_eq!(temp_file_ref.read_from(13).unwrap(), Vec::<u8>::new());
+		assert!(temp_file_ref.read_from(14).is_err());
+	}
+
+	#[test]
+	fn test_binary_ints() {
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write_bytes(&[0; 16]).unwrap();
+
+		temp_file_ref.write_u32_le(0, 0x12345678).unwrap();
+		assert_eq!(temp_file_ref.read_range(0, 4).unwrap(), vec![0x78, 0x56, 0x34, 0x12]);
+		assert_eq!(temp_file_ref.read_u32_le(0).unwrap(), 0x12345678);
+		assert_eq!(temp_file_ref.read_u32_be(0).unwrap(), 0x78563412);
+
+		temp_file_ref.write_u16_be(4, 0xABCD).unwrap();
+		assert_eq!(temp_file_ref.read_u16_be(4).unwrap(), 0xABCD);
+		assert_eq!(temp_file_ref.read_u16_le(4).unwrap(), 0xCDAB);
+
+		temp_file_ref.write_u64_be(8, 0x0102030405060708).unwrap();
+		assert_eq!(temp_file_ref.read_u64_be(8).unwrap(), 0x0102030405060708);
+		temp_file_ref.write_i64_le(8, -2).unwrap();
+		assert_eq!(temp_file_ref.read_i64_le(8).unwrap(), -2);
+		temp_file_ref.write_i32_be(0, -100).unwrap();
+		assert_eq!(temp_file_ref.read_i32_be(0).unwrap(), -100);
+
+		assert!(temp_file_ref.read_u64_le(12).is_err());
+		assert!(temp_file_ref.read_u16_le(16).is_err());
+	}
+
+	#[test]
+	fn test_binary_ints_offset_overflow() {
+		use std::io::{ Error, ErrorKind };
+
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write_bytes(&[0; 8]).unwrap();
+
+		let error:Box<dyn std::error::Error> = temp_file_ref.read_u32_le(u64::MAX - 1).unwrap_err();
+		assert_eq!(error.downcast_ref::<Error>().map(|error| error.kind()), Some(ErrorKind::InvalidInput));
+	}
+
+	#[test]
+	fn test_grep() {
+		let temp_file:TempFile = TempFile::new(Some("log"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("INFO started\nERROR disk full\nINFO retrying\nERROR code 42\n".to_string()).unwrap();
+
+		assert_eq!(temp_file_ref.grep("ERROR").unwrap(), vec![(2, "ERROR disk full".to_string()), (4, "ERROR code 42".to_string())]);
+		assert_eq!(temp_file_ref.grep("WARN").unwrap(), Vec::new());
+		assert!(FileRef::new(temp_file.path()).parent_dir().unwrap().grep("ERROR").is_err());
+	}
+
+	#[test]
+	#[cfg(feature="regex")]
+	fn test_grep_regex() {
+		let temp_file:TempFile = TempFile::new(Some("log"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("INFO started\nERROR disk full\nINFO retrying\nERROR code 42\n".to_string()).unwrap();
+
+		assert_eq!(temp_file_ref.grep_regex(r"\d+$").unwrap(), vec![(4, "ERROR code 42".to_string())]);
+		assert_eq!(temp_file_ref.grep_regex("^INFO").unwrap().len(), 2);
+		assert!(temp_file_ref.grep_regex("(").is_err());
+	}
+
+	#[test]
+	fn test_is_binary() {
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+
+		temp_file_ref.write("plain text\n".to_string()).unwrap();
+		assert!(!temp_file_ref.is_binary().unwrap());
+		temp_file_ref.write_bytes(&[0x7F, b'E', b'L', b'F', 0x02, 0x00]).unwrap();
+		assert!(temp_file_ref.is_binary().unwrap());
+		temp_file_ref.write_bytes(&[b"a".repeat(10000), vec![0]].concat()).unwrap();
+		assert!(!temp_file_ref.is_binary().unwrap());
+	}
+
+	#[test]
+	fn test_lines_count() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+
+		temp_file_ref.write(String::new()).unwrap();
+		assert_eq!(temp_file_ref.lines_count().unwrap(), 0);
+		temp_file_ref.write("one\ntwo\nthree\n".to_string()).unwrap();
+		assert_eq!(temp_file_ref.lines_count().unwrap(), 3);
+		temp_file_ref.write("one\r\ntwo\n\nfour".to_string()).unwrap();
+		assert_eq!(temp_file_ref.lines_count().unwrap(), 4);
+		temp_file_ref.write("line\n".repeat(10000)).unwrap();
+		assert_eq!(temp_file_ref.lines_count().unwrap(), 10000);
+	}
+
+	#[test]
+	fn test_byte_at() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Hello, world!".to_string()).unwrap();
+
+		assert_eq!(temp_file_ref.byte_at(0).unwrap(), Some(b'H'));
+		assert_eq!(temp_file_ref.byte_at(7).unwrap(), Some(b'w'));
+		assert_eq!(temp_file_ref.byte_at(12).unwrap(), Some(b'!'));
+		assert_eq!(temp_file_ref.byte_at(13).unwrap(), None);
+		assert_eq!(temp_file_ref.byte_at(1000).unwrap(), None);
+	}
+
+	#[test]
+	#[cfg(feature="hashing")]
+	fn test_hash_sha256() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("abc".to_string()).unwrap();
+		assert_eq!(temp_file_ref.hash_sha256().unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+		temp_file_ref.write_bytes(&vec![b'a'; 20000]).unwrap();
+		assert_eq!(temp_file_ref.hash_sha256().unwrap().len(), 64);
+		assert!(FileRef::new(temp_file.path()).parent_dir().unwrap().hash_sha256().is_err());
+	}
+
+	#[test]
+	#[cfg(feature="serde")]
+	fn test_read_json_pointer() {
+		let temp_file:TempFile = TempFile::new(Some("json"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("{ \"server\": { \"host\": \"localhost\", \"port\": 8080 } }".to_string()).unwrap();
+
+		assert_eq!(temp_file_ref.read_json_pointer("/server/port").unwrap(), serde_json::json!(8080));
+		assert_eq!(temp_file_ref.read_json_pointer("/server/host").unwrap(), serde_json::json!("localhost"));
+	}
+
+	#[test]
+	#[cfg(feature="json")]
+	fn test_read_and_write_json() {
+		use std::collections::BTreeMap;
+
+		let temp_dir:TempFile = TempFile::new(None);
+		let config_file:FileRef = FileRef::new(temp_dir.path()) + "/config/settings.json";
+		let mut config:BTreeMap<String, Vec<u32>> = BTreeMap::new();
+		config.insert("ports".to_string(), vec![80, 443]);
+		config.insert("retries".to_string(), vec![3]);
+
+		config_file.write_json(&config).unwrap();
+		assert_eq!(config_file.read_json::<BTreeMap<String, Vec<u32>>>().unwrap(), config);
+		assert!(config_file.read_json::<Vec<u32>>().is_err());
+		assert!((FileRef::new(temp_dir.path()) + "/missing.json").read_json::<Vec<u32>>().is_err());
+	}
+
+	#[test]
+	#[cfg(feature="serde")]
+	fn test_read_json_pointer_missing() {
+		let temp_file:TempFile = TempFile::new(Some("json"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("{ \"server\": { \"port\": 8080 } }".to_string()).unwrap();
+
+		assert!(temp_file_ref.read_json_pointer("/server/address").is_err());
+	}
+
+	#[test]
+	#[cfg(feature="block_device")]
+	fn test_read_aligned() {
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let sector_size:u64 = temp_file_ref.sector_size().unwrap_or(512);
+		let content:Vec<u8> = (0..sector_size * 4).map(|index| (index % 251) as u8).collect();
+		temp_file_ref.write_bytes(&content).unwrap();
+
+		assert!(temp_file_ref.sector_size().unwrap() > 0);
+		for (offset, len) in [(0, sector_size), (sector_size, sector_size * 2), (7, sector_size + 3), (sector_size * 3, sector_size)] {
+			assert_eq!(temp_file_ref.read_aligned(offset, len).unwrap(), temp_file_ref.read_range(offset, offset + len).unwrap());
+		}
+		assert!(temp_file_ref.read_aligned(sector_size * 3, sector_size + 1).is_err());
+	}
+
+	#[test]
+	#[cfg(feature="block_device")]
+	fn test_read_aligned_range_overflow() {
+		use std::io::{ Error, ErrorKind };
+
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write_bytes(&[0; 16]).unwrap();
+
+		let error:Box<dyn std::error::Error> = temp_file_ref.read_aligned(u64::MAX - 1, 4).unwrap_err();
+		assert_eq!(error.downcast_ref::<Error>().map(|error| error.kind()), Some(ErrorKind::InvalidInput));
+	}
+
+	#[test]
+	#[cfg(all(feature="sparse", target_os="linux"))]
+	fn test_sparse_ranges() {
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write_bytes(b"start").unwrap();
+		temp_file_ref.open_write(false).unwrap().set_len(64 * 1024 * 1024).unwrap();
+		temp_file_ref.write_bytes_to_range(64 * 1024 * 1024, b"end").unwrap();
+
+		let ranges:Vec<(u64, u64)> = temp_file_ref.sparse_ranges().unwrap();
+		assert_eq!(ranges.first().unwrap().0, 0);
+		assert_eq!(ranges.last().unwrap().1, 64 * 1024 * 1024 + 3);
+		assert!(ranges.len() >= 2, "Hole was reported as data: {ranges:?}");
+		assert!(ranges.iter().all(|(start, end)| *end <= 32 * 1024 * 1024 || *start > 32 * 1024 * 1024));
+	}
+
+	#[test]
+	fn test_reader() {
+		use std::io::{ Read, Seek, SeekFrom };
+
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Hello, world!".to_string()).unwrap();
+
+		let mut reader = temp_file_ref.reader().unwrap();
+		let mut buffer:[u8; 5] = [0; 5];
+		reader.seek(SeekFrom::Start(7)).unwrap();
+		reader.read_exact(&mut buffer).unwrap();
+		assert_eq!(buffer.to_vec(), temp_file_ref.read_range(7, 12).unwrap());
+
+		reader.seek(SeekFrom::Start(0)).unwrap();
+		reader.read_exact(&mut buffer).unwrap();
+		assert_eq!(&buffer, b"Hello");
+
+		assert!(FileRef::new(&(temp_file.path().to_owned() + ".missing.txt")).reader().is_err());
+	}
+
+	#[test]
+	fn test_open_read_and_write() {
+		use std::io::{ Read, Seek, SeekFrom, Write };
+
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/file.txt";
+		assert!(temp_file_ref.open_read().is_err());
+
+		temp_file_ref.open_write(true).unwrap().write_all(b"Hello, world!").unwrap();
+		temp_file_ref.open_write(false).unwrap().write_all(b"J").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Jello, world!");
+		temp_file_ref.open_write(true).unwrap().write_all(b"Hi").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Hi");
+
+		let mut file:std::fs::File = temp_file_ref.open_read().unwrap();
+		file.seek(SeekFrom::Start(1)).unwrap();
+		let mut contents:String = String::new();
+		file.read_to_string(&mut contents).unwrap();
+		assert_eq!(contents, "i");
+		assert!(temp_file_ref.parent_dir().unwrap().open_read().is_err());
+		assert!(temp_file_ref.parent_dir().unwrap().open_write(false).is_err());
+	}
+
 	#[test]
 	fn test_write_bytes_to_range() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -325,6 +1260,111 @@ mod tests {
 		assert_eq!(read_content, "Hello, Rust!!");
 	}
 
+	#[test]
+	fn test_write_atomic() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/config.toml";
+
+		temp_file_ref.write_atomic(b"retries = 3").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "retries = 3");
+
+		temp_file_ref.write_atomic(b"retries = 5").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "retries = 5");
+		assert_eq!(temp_file_ref.parent_dir().unwrap().list_files().len(), 1);
+		assert!(temp_file_ref.parent_dir().unwrap().write_atomic(b"data").is_err());
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_write_atomic_keeps_mode_and_symlink() {
+		use std::os::unix::fs::{ symlink, PermissionsExt };
+
+		let temp_dir:TempFile = TempFile::new(None);
+		let script:FileRef = FileRef::new(temp_dir.path()) + "/run.sh";
+		script.write_bytes(b"echo old").unwrap();
+		std::fs::set_permissions(script.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+		script.write_atomic(b"echo new").unwrap();
+		assert_eq!(script.read().unwrap(), "echo new");
+		assert_eq!(script.permissions().unwrap().mode() & 0o777, 0o755);
+
+		let link:FileRef = FileRef::new(temp_dir.path()) + "/link.sh";
+		symlink("run.sh", link.path()).unwrap();
+		link.write_atomic(b"echo linked").unwrap();
+		assert!(link.is_symlink());
+		assert_eq!(script.read().unwrap(), "echo linked");
+		assert_eq!(script.permissions().unwrap().mode() & 0o777, 0o755);
+	}
+
+	#[test]
+	fn test_write_atomic_durable() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/file.txt";
+
+		temp_file_ref.write_atomic_durable(b"first version").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "first version");
+
+		temp_file_ref.write_atomic_durable(b"second").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "second");
+		assert_eq!(temp_file_ref.parent_dir().unwrap().list_files().len(), 1);
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_splice_keeps_mode_and_symlink() {
+		use std::os::unix::fs::{ symlink, PermissionsExt };
+
+		let temp_dir:TempFile = TempFile::new(None);
+		let script:FileRef = FileRef::new(temp_dir.path()) + "/run.sh";
+		script.write_bytes(b"echo old").unwrap();
+		std::fs::set_permissions(script.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+		script.splice(5, 3, b"new").unwrap();
+		assert_eq!(script.read().unwrap(), "echo new");
+		assert_eq!(script.permissions().unwrap().mode() & 0o777, 0o755);
+
+		let link:FileRef = FileRef::new(temp_dir.path()) + "/link.sh";
+		symlink("run.sh", link.path()).unwrap();
+		link.splice(5, 3, b"linked").unwrap();
+		assert!(link.is_symlink());
+		assert_eq!(script.read().unwrap(), "echo linked");
+		assert_eq!(script.permissions().unwrap().mode() & 0o777, 0o755);
+	}
+
+	#[test]
+	fn test_splice() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Hello, you!".to_string()).unwrap();
+
+		// Grow the file.
+		temp_file_ref.splice(7, 3, b"world").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Hello, world!");
+		assert_eq!(temp_file_ref.bytes_size(), 13);
+
+		// Shrink the file.
+		temp_file_ref.splice(7, 5, b"you").unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Hello, you!");
+		assert_eq!(temp_file_ref.bytes_size(), 11);
+
+		// Out of bounds.
+		assert!(temp_file_ref.splice(10, 5, b"!").is_err());
+		assert_eq!(temp_file_ref.temp_sibling().unwrap().path(), temp_file_ref.parent_dir().unwrap().path().to_owned() + "/." + temp_file_ref.name() + ".tmp");
+	}
+
+	#[test]
+	fn test_splice_range_overflow() {
+		use std::io::{ Error, ErrorKind };
+
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Hello".to_string()).unwrap();
+
+		let error:Box<dyn std::error::Error> = temp_file_ref.splice(u64::MAX, 5, b"x").unwrap_err();
+		assert_eq!(error.downcast_ref::<Error>().map(|error| error.kind()), Some(ErrorKind::InvalidInput));
+		assert_eq!(temp_file_ref.read().unwrap(), "Hello");
+	}
+
 	#[test]
 	fn test_file_deletion() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -360,6 +1400,109 @@ mod tests {
 		target_file_ref.delete().unwrap();
 	}
 
+	#[test]
+	fn test_file_copy_with_progress() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source:FileRef = FileRef::new(temp_dir.path()) + "/source.bin";
+		let target:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/target.bin";
+		let data:Vec<u8> = (0..200_000).map(|index| (index % 251) as u8).collect();
+		source.write_bytes(&data).unwrap();
+
+		let mut progress:Vec<(u64, u64)> = Vec::new();
+		assert_eq!(source.copy_to_with_progress(&target, |copied, total| progress.push((copied, total))).unwrap(), 200_000);
+		assert_eq!(target.read_bytes().unwrap(), data);
+		assert!(progress.len() > 1);
+		assert!(progress.windows(2).all(|pair| pair[0].0 < pair[1].0));
+		assert_eq!(progress.last(), Some(&(200_000, 200_000)));
+		assert!((FileRef::new(temp_dir.path()) + "/missing.bin").copy_to_with_progress(&target, |_, _| {}).is_err());
+	}
+
+	#[test]
+	fn test_file_copy_buffered() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source:FileRef = FileRef::new(temp_dir.path()) + "/source.bin";
+		let target:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/target.bin";
+		let data:Vec<u8> = (0..3_000_000).map(|index| (index % 253) as u8).collect();
+		source.write_bytes(&data).unwrap();
+
+		assert_eq!(source.copy_to_buffered(&target, 1024 * 1024).unwrap(), 3_000_000);
+		assert_eq!(target.read_bytes().unwrap(), data);
+		assert!(source.copy_to_buffered(&target, 0).is_err());
+	}
+
+	#[test]
+	fn test_file_copy_resumable() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/source.bin";
+		let target_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/target.bin";
+		let content:Vec<u8> = (0..20000).map(|index| (index % 251) as u8).collect();
+		source_file_ref.write_bytes(&content).unwrap();
+
+		// Resume partial copy.
+		target_file_ref.write_bytes(&content[..12345]).unwrap();
+		assert_eq!(source_file_ref.copy_to_resumable(&target_file_ref).unwrap(), 20000 - 12345);
+		assert_eq!(target_file_ref.read_bytes().unwrap(), content);
+
+		// Already complete.
+		assert_eq!(source_file_ref.copy_to_resumable(&target_file_ref).unwrap(), 0);
+
+		// Mismatching prefix.
+		target_file_ref.write_bytes(b"other").unwrap();
+		assert_eq!(source_file_ref.copy_to_resumable(&target_file_ref).unwrap(), 20000);
+		assert_eq!(target_file_ref.read_bytes().unwrap(), content);
+
+		// Missing target.
+		target_file_ref.delete().unwrap();
+		assert_eq!(source_file_ref.copy_to_resumable(&target_file_ref).unwrap(), 20000);
+		assert_eq!(target_file_ref.read_bytes().unwrap(), content);
+	}
+
+	#[test]
+	fn test_split_into_and_join_parts() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/source.bin";
+		let parts_dir:FileRef = FileRef::new(temp_dir.path()) + "/parts";
+		let joined_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/joined.bin";
+		let content:Vec<u8> = (0..2500).map(|index| (index % 251) as u8).collect();
+		source_file_ref.write_bytes(&content).unwrap();
+
+		let parts:Vec<FileRef> = source_file_ref.split_into(1000, &parts_dir).unwrap();
+		assert_eq!(parts, vec![parts_dir.clone() + "/source.bin.part0", parts_dir.clone() + "/source.bin.part1", parts_dir.clone() + "/source.bin.part2"]);
+		assert_eq!(parts.iter().map(|part| part.bytes_size()).collect::<Vec<u64>>(), vec![1000, 1000, 500]);
+
+		FileRef::join_parts(&parts, &joined_file_ref).unwrap();
+		assert_eq!(joined_file_ref.read_bytes().unwrap(), content);
+		assert!(source_file_ref.split_into(0, &parts_dir).is_err());
+	}
+
+	#[test]
+	fn test_dir_copy() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source_dir:FileRef = FileRef::new(temp_dir.path()) + "/source";
+		let target_dir:FileRef = FileRef::new(temp_dir.path()) + "/target";
+		(source_dir.clone() + "/file1.txt").write("12345".to_string()).unwrap();
+		(source_dir.clone() + "/sub_dir/file2.txt").write("123".to_string()).unwrap();
+		(source_dir.clone() + "/empty_dir").create_dir().unwrap();
+
+		assert_eq!(source_dir.copy_to(&target_dir).unwrap(), 8);
+		assert_eq!((target_dir.clone() + "/file1.txt").read().unwrap(), "12345");
+		assert_eq!((target_dir.clone() + "/sub_dir/file2.txt").read().unwrap(), "123");
+		assert!((target_dir.clone() + "/empty_dir").is_dir());
+		assert!((source_dir.clone() + "/sub_dir/file2.txt").exists());
+
+		assert!(source_dir.copy_to(&(source_dir.clone() + "/inner")).is_err());
+	}
+
+	#[test]
+	fn test_missing_dir_copy() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source_dir:FileRef = FileRef::new(temp_dir.path()) + "/missing_dir";
+		let target_dir:FileRef = FileRef::new(temp_dir.path()) + "/target";
+
+		assert!(source_dir.copy_to(&target_dir).unwrap_err().to_string().contains("does not exist"));
+		assert!(!target_dir.exists());
+	}
+
 	#[test]
 	fn test_file_move() {
 		let temp_file:TempFile = TempFile::new(Some("txt"));
@@ -378,4 +1521,153 @@ mod tests {
 
 		target_file_ref.delete().unwrap();
 	}
+
+	#[test]
+	fn test_file_move_with_progress() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source:FileRef = FileRef::new(temp_dir.path()) + "/source.bin";
+		let target:FileRef = FileRef::new(temp_dir.path()) + "/sub_dir/target.bin";
+		source.write_bytes(&[7; 1000]).unwrap();
+
+		let mut progress:Vec<(u64, u64)> = Vec::new();
+		source.move_to_with_progress(&target, |moved, total| progress.push((moved, total))).unwrap();
+		assert_eq!(progress, vec![(1000, 1000)]);
+		assert!(!source.exists());
+		assert_eq!(target.read_bytes().unwrap(), vec![7; 1000]);
+		assert!(source.move_to_with_progress(&target, |_, _| {}).is_err());
+	}
+
+	#[test]
+	fn test_file_move_by_copy() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source:FileRef = FileRef::new(temp_dir.path()) + "/source.bin";
+		let target:FileRef = FileRef::new(temp_dir.path()) + "/other_drive/target.bin";
+		let data:Vec<u8> = (0..150_000).map(|index| (index % 256) as u8).collect();
+		source.write_bytes(&data).unwrap();
+
+		let mut progress:Vec<(u64, u64)> = Vec::new();
+		source.move_by_copy(&target, |moved, total| progress.push((moved, total))).unwrap();
+		assert!(progress.len() > 1);
+		assert_eq!(progress.last(), Some(&(150_000, 150_000)));
+		assert!(!source.exists());
+		assert_eq!(target.read_bytes().unwrap(), data);
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_file_move_by_copy_keeps_mode() {
+		use std::os::unix::fs::PermissionsExt;
+
+		let temp_dir:TempFile = TempFile::new(None);
+		let source:FileRef = FileRef::new(temp_dir.path()) + "/run.sh";
+		let copy_target:FileRef = FileRef::new(temp_dir.path()) + "/copy/run.sh";
+		let move_target:FileRef = FileRef::new(temp_dir.path()) + "/other_drive/run.sh";
+		source.write_bytes(b"echo hello").unwrap();
+		std::fs::set_permissions(source.path(), std::fs::Permissions::from_mode(0o750)).unwrap();
+
+		source.copy_to_buffered(&copy_target, 4).unwrap();
+		assert_eq!(copy_target.permissions().unwrap().mode() & 0o777, 0o750);
+
+		source.move_by_copy(&move_target, |_, _| {}).unwrap();
+		assert!(!source.exists());
+		assert_eq!(move_target.permissions().unwrap().mode() & 0o777, 0o750);
+	}
+
+	#[test]
+	fn test_dir_move() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source_dir:FileRef = FileRef::new(temp_dir.path()) + "/source";
+		let target_dir:FileRef = FileRef::new(temp_dir.path()) + "/nested/target";
+		(source_dir.clone() + "/sub_dir/file.txt").write("content".to_string()).unwrap();
+
+		source_dir.move_to(&target_dir).unwrap();
+		assert!(!source_dir.exists());
+		assert_eq!((target_dir.clone() + "/sub_dir/file.txt").read().unwrap(), "content");
+	}
+
+
+
+	#[test]
+	fn test_rename() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let source_file_ref:FileRef = FileRef::new(temp_dir.path()) + "/original.txt";
+		source_file_ref.write("content".to_string()).unwrap();
+
+		let renamed_file_ref:FileRef = source_file_ref.rename("renamed.txt").unwrap();
+		assert_eq!(renamed_file_ref, FileRef::new(temp_dir.path()) + "/renamed.txt");
+		assert!(!source_file_ref.exists());
+		assert_eq!(renamed_file_ref.read().unwrap(), "content");
+
+		assert!(renamed_file_ref.rename("sub_dir/renamed.txt").is_err());
+		assert!(renamed_file_ref.rename("sub_dir\\renamed.txt").is_err());
+		assert!(source_file_ref.rename("other.txt").is_err());
+	}
+
+	#[test]
+	fn test_flatten() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let root:FileRef = FileRef::new(temp_dir.path());
+		for (path, contents) in [("/a.txt", "root"), ("/sub1/a.txt", "sub1"), ("/sub1/deep/b.txt", "b"), ("/sub2/c.txt", "c"), ("/sub2/deep/a.txt", "deep")] {
+			(root.clone() + path).write(contents.to_string()).unwrap();
+		}
+
+		assert_eq!(root.flatten(ConflictPolicy::Rename, true).unwrap(), 4);
+		let mut names:Vec<String> = root.list_files().iter().map(|file| file.name().to_owned()).collect();
+		names.sort();
+		assert_eq!(names, vec!["a (1).txt", "a (2).txt", "a.txt", "b.txt", "c.txt"]);
+		assert!(root.list_dirs().is_empty());
+		assert_eq!((root.clone() + "/a.txt").read().unwrap(), "root");
+		let mut contents:Vec<String> = ["/a (1).txt", "/a (2).txt"].iter().map(|path| (root.clone() + path).read().unwrap()).collect();
+		contents.sort();
+		assert_eq!(contents, vec!["deep", "sub1"]);
+	}
+
+	#[test]
+	fn test_flatten_skip_and_overwrite() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let root:FileRef = FileRef::new(temp_dir.path());
+		for (path, contents) in [("/a.txt", "root"), ("/sub1/a.txt", "sub1"), ("/sub2/b.txt", "b")] {
+			(root.clone() + path).write(contents.to_string()).unwrap();
+		}
+
+		assert_eq!(root.flatten(ConflictPolicy::Skip, true).unwrap(), 1);
+		assert_eq!((root.clone() + "/a.txt").read().unwrap(), "root");
+		assert!((root.clone() + "/sub1/a.txt").exists());
+		assert!(!(root.clone() + "/sub2").exists());
+
+		assert_eq!(root.flatten(ConflictPolicy::Overwrite, false).unwrap(), 1);
+		assert_eq!((root.clone() + "/a.txt").read().unwrap(), "sub1");
+		assert!((root.clone() + "/sub1").exists());
+		assert!((root.clone() + "/missing_dir").flatten(ConflictPolicy::Skip, false).is_err());
+	}
+
+
+
+	/* QUICK SCANNER TESTS */
+
+	#[test]
+	fn test_entry_count() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_dir_ref:FileRef = FileRef::new(temp_dir.path());
+		for path in ["/subdir1/sub_subdir1", "/subdir2", "/file1.txt", "/subdir1/file2.txt", "/subdir1/sub_subdir1/file3.txt", "/subdir2/file4.txt"] {
+			(temp_dir_ref.clone() + path).create().unwrap();
+		}
+
+		assert_eq!(temp_dir_ref.entry_count().unwrap(), 3);
+		assert_eq!(temp_dir_ref.entry_count_recursive().unwrap(), 7);
+		assert_eq!((temp_dir_ref.clone() + "/subdir1").entry_count().unwrap(), 2);
+		assert!((temp_dir_ref.clone() + "/missing_dir").entry_count().is_err());
+	}
+
+	#[test]
+	fn test_name_exists_ignore_case() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let temp_dir_ref:FileRef = FileRef::new(temp_dir.path());
+		(temp_dir_ref.clone() + "/FOO.txt").create().unwrap();
+
+		let found:FileRef = temp_dir_ref.name_exists_ignore_case("foo.txt").unwrap().unwrap();
+		assert_eq!(found.name(), "FOO.txt");
+		assert!(temp_dir_ref.name_exists_ignore_case("bar.txt").unwrap().is_none());
+		assert!((temp_dir_ref.clone() + "/missing_dir").name_exists_ignore_case("foo.txt").is_err());
+	}
 }
\ No newline at end of file
@@ -1,14 +1,38 @@
-use crate::{ FileRef, SEPARATOR };
+use std::{ cell::OnceCell, error::Error, collections::HashMap, fs::{ DirEntry, Metadata }, hash::{ DefaultHasher, Hasher }, path::{ Path, PathBuf }, sync::{ Arc, atomic::{ AtomicBool, AtomicU32, Ordering } } };
+use crate::{ FileErrors, FileRef, SEPARATOR };
 
 
 
 type ResultFilter = Box<dyn Fn(&FileRef) -> bool>;
+type MetadataFilter = Box<dyn Fn(&FileRef, &Metadata) -> bool>;
 struct ScanSettings {
 	include_self:bool,
 	include_files:bool,
 	include_dirs:bool,
-	results_filter:ResultFilter,
-	recurse_filter:ResultFilter
+	post_order:bool,
+	sorted:bool,
+	follow_symlinks:bool,
+	results_filter:Option<ResultFilter>,
+	metadata_filter:Option<MetadataFilter>,
+	recurse:bool,
+	recurse_filter:Option<ResultFilter>,
+	max_depth:Option<usize>,
+	cancel_token:Option<CancelToken>,
+	progress:Option<Arc<AtomicU32>>,
+	#[cfg(feature="rayon")]
+	thread_count:Option<usize>
+}
+impl ScanSettings {
+
+	/// Check if an entry passes the result filter. Without a result filter all entries pass.
+	fn accepts(&self, entry:&FileRef) -> bool {
+		self.results_filter.as_ref().is_none_or(|results_filter| results_filter(entry))
+	}
+
+	/// Check if a dir passes the recurse filter. Without a recurse filter all dirs pass, as long as recursing is enabled.
+	fn recurses_into(&self, dir:&FileRef) -> bool {
+		self.recurse && self.recurse_filter.as_ref().is_none_or(|recurse_filter| recurse_filter(dir))
+	}
 }
 
 
@@ -21,7 +45,7 @@ impl FileScanner {
 
 	/* CONSTRUCTOR METHODS */
 
-	/// Create a new filter.
+	/// Create a new filter. When the root is a file instead of a dir, the scanner yields only that file, given either `include_self` or `include_files` is set.
 	pub fn new(root_dir:&FileRef) -> FileScanner {
 		let root_dir:FileRef = root_dir.clone().absolute().trim_end_matches(SEPARATOR);
 		FileScanner {
@@ -29,10 +53,20 @@ impl FileScanner {
 				include_self: false,
 				include_files: false,
 				include_dirs: false,
-				results_filter: Box::new(|_| true),
-				recurse_filter: Box::new(|_| false),
+				post_order: false,
+				sorted: false,
+				follow_symlinks: false,
+				results_filter: None,
+				metadata_filter: None,
+				recurse: false,
+				recurse_filter: None,
+				max_depth: None,
+				cancel_token: None,
+				progress: None,
+				#[cfg(feature="rayon")]
+				thread_count: None
 			},
-			sub_dir_scanner: SubDirScanner::new(root_dir)
+			sub_dir_scanner: SubDirScanner::new(root_dir, 0, true)
 		}
 	}
 
@@ -54,22 +88,267 @@ impl FileScanner {
 		self
 	}
 
+	/// Return self with a setting to yield each dir only after all of its contents, rather than before. The source dir, if included, comes last.
+	pub fn post_order(mut self) -> Self {
+		self.scan_settings.post_order = true;
+		self
+	}
+
+	/// Return self with a setting to sort the entries of each dir by name before yielding them, making the order of results the same on every platform.
+	pub fn sorted(mut self) -> Self {
+		self.scan_settings.sorted = true;
+		self
+	}
+
+	/// Return self with a setting to recurse into symlinked dirs or not. Off by default, as symlinks can form loops. Symlinked dirs are still yielded as dirs when not followed.
+	pub fn follow_symlinks(mut self, follow_symlinks:bool) -> Self {
+		self.scan_settings.follow_symlinks = follow_symlinks;
+		self
+	}
+
 	/// Return self with a result filter. Overwrites the default filter function to filter out entries during the search process, rather than after being returned.
 	pub fn filter<T>(mut self, filter:T) -> Self where T:Fn(&FileRef) -> bool + 'static {
-		self.scan_settings.results_filter = Box::new(filter);
+		self.scan_settings.results_filter = Some(Box::new(filter));
 		self
 	}
 
+	/// Return self with a filter on the metadata of entries, applied on top of the result filter. The metadata is fetched once per entry during the scan, which also determines whether the entry is a dir, so the filter causes no additional metadata lookups.
+	pub fn filter_metadata<T>(mut self, filter:T) -> Self where T:Fn(&FileRef, &Metadata) -> bool + 'static {
+		self.scan_settings.metadata_filter = Some(Box::new(filter));
+		self
+	}
+
+	/// Return self with a filter that only accepts entries of which the name matches the given regex. Applies on top of the current result filter. Errors if the regex does not compile.
+	#[cfg(feature="regex")]
+	pub fn name_regex(mut self, pattern:&str) -> Result<Self, Box<dyn Error>> {
+		let regex:regex::Regex = regex::Regex::new(pattern)?;
+		let results_filter:Option<ResultFilter> = self.scan_settings.results_filter.take();
+		self.scan_settings.results_filter = Some(Box::new(move |entry| regex.is_match(entry.name()) && results_filter.as_ref().is_none_or(|results_filter| results_filter(entry))));
+		Ok(self)
+	}
+
 	/// Return self with a setting to recurse into sub-dirs.
-	pub fn recurse(self) -> Self {
-		self.recurse_filter(|_| true)
+	pub fn recurse(mut self) -> Self {
+		self.scan_settings.recurse = true;
+		self.scan_settings.recurse_filter = None;
+		self
 	}
 
 	/// Return self with a recurse filter.
 	pub fn recurse_filter<T>(mut self, filter:T) -> Self where T:Fn(&FileRef) -> bool + 'static {
-		self.scan_settings.recurse_filter = Box::new(filter);
+		self.scan_settings.recurse = true;
+		self.scan_settings.recurse_filter = Some(Box::new(filter));
+		self
+	}
+
+
+
+	/// Return self with a maximum depth to recurse to. Depth 0 only scans the root's direct entries, depth 1 also scans the entries of its sub-dirs and so on.
+	pub fn max_depth(mut self, max_depth:usize) -> Self {
+		self.scan_settings.max_depth = Some(max_depth);
+		self
+	}
+
+	/// Return self with a cancel token. Once the token is cancelled, the scanner stops yielding results.
+	pub fn with_cancel(mut self, token:CancelToken) -> Self {
+		self.scan_settings.cancel_token = Some(token);
+		self
+	}
+
+
+
+	/// Return self with a progress counter, which the scanner keeps updated with an estimated completion percentage from 0 to 100 that can be polled during iteration. The estimate is only approximate, as it is based on how many of the sub-dirs of the root have been fully scanned, regardless of their size. Reaches 100 once the scan is complete.
+	pub fn with_progress(mut self, progress:Arc<AtomicU32>) -> Self {
+		self.scan_settings.progress = Some(progress);
+		self
+	}
+
+
+
+	/// Return self with the amount of threads `collect_parallel` scans on. The scan then runs in its own thread pool of that size instead of the global rayon pool, so scans of spinning disks or network shares can limit parallelism. 0 uses rayon's default amount of threads.
+	#[cfg(feature="rayon")]
+	pub fn par_threads(mut self, thread_count:usize) -> Self {
+		self.scan_settings.thread_count = Some(thread_count);
 		self
 	}
+
+
+
+	/* TERMINAL METHODS */
+
+	/// Collect the matched files, keeping only one file per distinct content. Files are bucketed by size and a quick hash of their contents, and only discarded after a byte-by-byte comparison with a kept file in the same bucket. Of equal files, the lexicographically first path is kept.
+	pub fn unique_by_content(self) -> Result<Vec<FileRef>, Box<dyn Error>> {
+		let mut files:Vec<FileRef> = self.collect();
+		files.retain(|file| file.is_file());
+		files.sort_by(|a, b| a.path().cmp(b.path()));
+
+		let mut buckets:HashMap<(u64, u64), Vec<FileRef>> = HashMap::new();
+		let mut unique_files:Vec<FileRef> = Vec::new();
+		for file in files {
+			let bucket:&mut Vec<FileRef> = buckets.entry((file.bytes_size(), Self::content_hash(&file)?)).or_default();
+			let mut is_duplicate:bool = false;
+			for kept_file in bucket.iter() {
+				if kept_file.starts_with_contents_of(&file)? {
+					is_duplicate = true;
+					break;
+				}
+			}
+			if !is_duplicate {
+				bucket.push(file.clone());
+				unique_files.push(file);
+			}
+		}
+		Ok(unique_files)
+	}
+
+	/// Collect the matched entries grouped by the dir they are in. Entries without a parent dir, like a disk root, are grouped under an empty path.
+	pub fn group_by_parent(self) -> HashMap<FileRef, Vec<FileRef>> {
+		let mut groups:HashMap<FileRef, Vec<FileRef>> = HashMap::new();
+		for entry in self {
+			let parent_dir:FileRef = entry.parent_dir().unwrap_or_else(|_| FileRef::new(""));
+			groups.entry(parent_dir).or_default().push(entry);
+		}
+		groups
+	}
+
+	/// Yield the matched entries as paths relative to the scanned dir. The scanned dir itself, if included, becomes an empty path.
+	pub fn relative_to_root(self) -> impl Iterator<Item = FileRef> {
+		let root_dir:FileRef = self.sub_dir_scanner.dir.clone();
+		self.map(move |entry| root_dir.relative_path_to(&entry))
+	}
+
+	/// Yield the matched entries that are symlinks, each paired with the path it points to. Relative targets are resolved against the dir of the symlink. Symlinks are matched as the type of their target, broken symlinks as files.
+	pub fn symlinks(self) -> impl Iterator<Item = (FileRef, FileRef)> {
+		self.filter_map(|symlink| {
+			if !symlink.is_symlink() {
+				return None;
+			}
+			let target:String = std::fs::read_link(symlink.path()).ok()?.to_str()?.to_owned();
+			let target:FileRef = FileRef::new(&target);
+			let target:FileRef = if target.is_absolute_path() { target } else { symlink.parent_dir().ok()? + SEPARATOR + target.path() };
+			Some((symlink, target))
+		})
+	}
+
+	/// Collect the matched entries into a tree following the dir hierarchy, with the scanned dir as root. Dirs that were not matched themselves are only included when they contain matched entries. Children are sorted by path.
+	pub fn into_tree(self) -> FileTree {
+		let root_dir:FileRef = self.sub_dir_scanner.dir.clone();
+		let mut tree:FileTree = FileTree { file: root_dir.clone(), children: Vec::new() };
+		for entry in self {
+			if entry != root_dir {
+				tree.insert(entry);
+			}
+		}
+		tree.sort();
+		tree
+	}
+
+	/// Replace all occurrences of a string in the matched text files, writing each changed file atomically. Binary files are skipped. Keeps going when a file fails and returns the error of every failed file afterwards, the other files are still changed. Returns the number of files changed.
+	pub fn replace_in_all(self, from:&str, to:&str) -> Result<usize, FileErrors> {
+		let mut changed_count:usize = 0;
+		let mut errors:FileErrors = Vec::new();
+		for file in self {
+			if file.is_dir() {
+				continue;
+			}
+			match Self::replace_in_file(&file, from, to) {
+				Ok(true) => changed_count += 1,
+				Ok(false) => {},
+				Err(error) => errors.push((file, error))
+			}
+		}
+		if errors.is_empty() {
+			Ok(changed_count)
+		} else {
+			Err(errors)
+		}
+	}
+
+	/// Replace all occurrences of a string in a single text file. Returns whether the file was changed.
+	fn replace_in_file(file:&FileRef, from:&str, to:&str) -> Result<bool, Box<dyn Error>> {
+		if file.is_binary()? {
+			return Ok(false);
+		}
+		let contents:String = file.read()?;
+		if !contents.contains(from) {
+			return Ok(false);
+		}
+		file.write_atomic(contents.replace(from, to).as_bytes())?;
+		Ok(true)
+	}
+
+	/// Pick a random sample of up to n matched entries using reservoir sampling, so the results are never collected in full. The same seed gives the same sample for the same results.
+	pub fn sample(self, n:usize, seed:u64) -> Vec<FileRef> {
+		let mut random_state:u64 = seed;
+		let mut reservoir:Vec<FileRef> = Vec::with_capacity(n);
+		for (index, entry) in self.enumerate() {
+			if index < n {
+				reservoir.push(entry);
+			} else {
+				let replace_index:usize = (Self::next_random(&mut random_state) % (index as u64 + 1)) as usize;
+				if replace_index < n {
+					reservoir[replace_index] = entry;
+				}
+			}
+		}
+		reservoir
+	}
+
+	/// Collect the matched entries, scanning sub-dirs on multiple threads at once. As the filters are shared between threads, they are given here and have to be `Send + Sync`. The result filter works like `filter`, the recurse filter like `recurse_filter`. Errors if the scanner itself has a result, metadata or recurse filter set, as those can not be shared between threads, or if the thread pool set by `par_threads` can not be created. The order of the results is not preserved, so `sorted` and `post_order` have no effect. Always scans from the start, regardless of entries already taken from the scanner.
+	#[cfg(feature="rayon")]
+	pub fn collect_parallel<T, U>(mut self, results_filter:T, recurse_filter:U) -> Result<Vec<FileRef>, Box<dyn Error>> where T:Fn(&FileRef) -> bool + Send + Sync, U:Fn(&FileRef) -> bool + Send + Sync {
+		if self.scan_settings.results_filter.is_some() || self.scan_settings.metadata_filter.is_some() || self.scan_settings.recurse_filter.is_some() {
+			return Err(format!("Could not scan \"{}\" in parallel. Filters set on the scanner can not be shared between threads, pass them to collect_parallel instead.", self.sub_dir_scanner.dir.path()).into());
+		}
+		let parallel_settings:ParallelScanSettings = ParallelScanSettings {
+			include_files: self.scan_settings.include_files,
+			include_dirs: self.scan_settings.include_dirs,
+			follow_symlinks: self.scan_settings.follow_symlinks,
+			max_depth: self.scan_settings.max_depth,
+			cancel_token: self.scan_settings.cancel_token.clone(),
+			results_filter: &results_filter,
+			recurse_filter: &recurse_filter
+		};
+		let mut results:Vec<FileRef> = self.sub_dir_scanner.get_self(&self.scan_settings).filter(|dir| results_filter(dir)).into_iter().collect();
+		let scan = || SubDirScanner::scan_parallel(&self.sub_dir_scanner.dir, 0, &parallel_settings);
+		results.extend(match self.scan_settings.thread_count {
+			Some(thread_count) => rayon::ThreadPoolBuilder::new().num_threads(thread_count).build()?.install(scan),
+			None => scan()
+		});
+		Ok(results)
+	}
+
+	/// Count the matched entries without collecting them. Cheaper than `collect().len()` or `count()`, which build, queue and return a FileRef for every entry, while this only builds one when a filter has to look at the entry and stores nothing. Both read the same dir listings, so the disk access is the same. Entries of which the path is not valid UTF-8 are skipped, like in the scan itself. Always counts from the start, regardless of entries already taken from the scanner.
+	pub fn count_matching(mut self) -> usize {
+		let self_count:usize = self.sub_dir_scanner.get_self(&self.scan_settings).map(|_| 1).unwrap_or(0);
+		self_count + SubDirScanner::count_matching(Path::new(self.sub_dir_scanner.dir.path()), 0, &self.scan_settings)
+	}
+
+	/// Get the next number from a splitmix64 random number generator.
+	fn next_random(state:&mut u64) -> u64 {
+		*state = state.wrapping_add(0x9E3779B97F4A7C15);
+		let mut value:u64 = *state;
+		value = (value ^ (value >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+		value = (value ^ (value >> 27)).wrapping_mul(0x94D049BB133111EB);
+		value ^ (value >> 31)
+	}
+
+	/// Hash the contents of a file, reading it in chunks.
+	fn content_hash(file:&FileRef) -> Result<u64, Box<dyn Error>> {
+		use std::{ fs::File, io::Read };
+
+		let mut hasher:DefaultHasher = DefaultHasher::new();
+		let mut handle:File = File::open(file.path())?;
+		let mut buffer:[u8; 8192] = [0; 8192];
+		loop {
+			let bytes_read:usize = handle.read(&mut buffer)?;
+			if bytes_read == 0 {
+				break;
+			}
+			hasher.write(&buffer[..bytes_read]);
+		}
+		Ok(hasher.finish())
+	}
 }
 impl Iterator for FileScanner {
 	type Item = FileRef;
@@ -81,50 +360,122 @@ impl Iterator for FileScanner {
 
 
 
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+impl CancelToken {
+
+	/// Create a new token that is not cancelled yet.
+	pub fn new() -> CancelToken {
+		CancelToken::default()
+	}
+
+	/// Cancel the token, stopping all scanners using it.
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::SeqCst);
+	}
+
+	/// Check if the token has been cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::SeqCst)
+	}
+}
+
+
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct FileTree {
+	pub file:FileRef,
+	pub children:Vec<FileTree>
+}
+impl FileTree {
+
+	/// Insert an entry somewhere below this node, creating the nodes of the dirs in between.
+	fn insert(&mut self, entry:FileRef) {
+		let prefix:String = self.file.path().to_owned() + SEPARATOR;
+		let relative_path:&str = match entry.path().strip_prefix(&prefix) {
+			Some(relative_path) => relative_path,
+			None => return
+		};
+		let mut node:&mut FileTree = self;
+		for name in relative_path.split(SEPARATOR) {
+			let child_file:FileRef = node.file.clone() + &(SEPARATOR.to_owned() + name);
+			let child_index:usize = match node.children.iter().position(|child| child.file == child_file) {
+				Some(index) => index,
+				None => {
+					node.children.push(FileTree { file: child_file, children: Vec::new() });
+					node.children.len() - 1
+				}
+			};
+			node = &mut node.children[child_index];
+		}
+	}
+
+	/// Sort the children of this node and all nodes below it by path.
+	fn sort(&mut self) {
+		self.children.sort_by(|a, b| a.file.path().cmp(b.file.path()));
+		self.children.iter_mut().for_each(|child| child.sort());
+	}
+}
+
+
+
 struct SubDirScanner {
 	dir:FileRef,
+	depth:usize,
+	accepted:bool,
 	parsed_self:bool,
 	files_in_dir:Option<Vec<FileRef>>,
 	dirs_in_dir:Option<Vec<FileRef>>,
-	sub_scanners:Option<Vec<SubDirScanner>>
+	sub_scanners:Option<Vec<SubDirScanner>>,
+	sub_scanner_count:usize
 }
 impl SubDirScanner {
 
-	/// Create a new recursive sub-dir scanner.
-	fn new(dir:FileRef) -> SubDirScanner {
+	/// Create a new recursive sub-dir scanner. The depth is the amount of dirs between the root and this dir, accepted is whether the dir itself passed the metadata filter.
+	fn new(dir:FileRef, depth:usize, accepted:bool) -> SubDirScanner {
 		SubDirScanner {
 			dir,
+			depth,
+			accepted,
 			parsed_self: false,
 			files_in_dir: None,
 			dirs_in_dir: None,
-			sub_scanners: None
+			sub_scanners: None,
+			sub_scanner_count: 0
 		}
 	}
 
 	/// Get the next file.
 	fn get(&mut self, scan_settings:&ScanSettings, is_root:bool) -> Option<FileRef> {
 
+		// Stop when cancelled.
+		if scan_settings.cancel_token.as_ref().is_some_and(|token| token.is_cancelled()) {
+			return None;
+		}
+
 		// Try Self.
-		if is_root && scan_settings.include_self && !self.parsed_self {
-			self.parsed_self = true;
-			if (scan_settings.results_filter)(&self.dir) {
-				return Some(self.dir.clone());
+		if is_root && !scan_settings.post_order {
+			if let Some(result) = self.get_self(scan_settings) {
+				return Some(result);
 			}
 		}
 
 		// Scan entries in this dir.
 		if self.files_in_dir.is_none() || self.sub_scanners.is_none() || self.sub_scanners.is_none() {
-			let dir_entries:Vec<FileRef> = Self::get_dir_raw_entries(&self.dir);
-			let mut files:Vec<FileRef> = Vec::new();
-			let mut dirs:Vec<FileRef> = Vec::new();
-			for entry in dir_entries {
-				if entry.is_file() {
-					files.push(entry);
-				} else {
-					dirs.push(entry);
-				}
+			let mut dir_entries:Vec<RawEntry> = Self::get_dir_raw_entries(&self.dir, scan_settings.metadata_filter.as_ref());
+			if scan_settings.sorted {
+				dir_entries.sort_by(|a, b| a.file.name().cmp(b.file.name()));
 			}
-			self.sub_scanners = Some(dirs.iter().filter(|dir| (scan_settings.recurse_filter)(dir)).map(|dir| SubDirScanner::new(dir.clone())).collect::<Vec<SubDirScanner>>());
+			let (dirs, files):(Vec<RawEntry>, Vec<RawEntry>) = dir_entries.into_iter().partition(|entry| entry.is_dir);
+			let files:Vec<FileRef> = files.into_iter().filter(|entry| entry.accepted).map(|entry| entry.file).collect();
+			let may_recurse:bool = scan_settings.max_depth.is_none_or(|max_depth| self.depth < max_depth);
+			let recurses_into = |dir:&RawEntry| may_recurse && (scan_settings.follow_symlinks || !dir.is_symlink) && scan_settings.recurses_into(&dir.file);
+			self.sub_scanners = Some(dirs.iter().filter(|dir| recurses_into(dir)).map(|dir| SubDirScanner::new(dir.file.clone(), self.depth + 1, dir.accepted)).collect::<Vec<SubDirScanner>>());
+			self.sub_scanner_count = self.sub_scanners.as_ref().map(|sub_scanners| sub_scanners.len()).unwrap_or(0);
+			let dirs:Vec<FileRef> = dirs.iter()
+				.filter(|dir| dir.accepted && !(scan_settings.post_order && recurses_into(dir))) // In post-order, dirs recursed into are yielded after their sub-scanner is done.
+				.map(|dir| dir.file.clone())
+				.collect();
 			self.files_in_dir = Some(files);
 			self.dirs_in_dir = Some(dirs);
 		}
@@ -134,7 +485,7 @@ impl SubDirScanner {
 			if let Some(files) = &mut self.files_in_dir {
 				while !files.is_empty() {
 					let file:FileRef = files.remove(0);
-					if (scan_settings.results_filter)(&file) {
+					if scan_settings.accepts(&file) {
 						return Some(file);
 					}
 				}
@@ -146,7 +497,7 @@ impl SubDirScanner {
 			if let Some(dirs) = &mut self.dirs_in_dir {
 				while !dirs.is_empty() {
 					let dir:FileRef = dirs.remove(0);
-					if (scan_settings.results_filter)(&dir) {
+					if scan_settings.accepts(&dir) {
 						return Some(dir);
 					}
 				}
@@ -160,24 +511,170 @@ impl SubDirScanner {
 				if let Some(result) = sub_scanner.get(&scan_settings, false) {
 					return Some(result);
 				}
-				sub_scanners.remove(0);
+				let finished_scanner:SubDirScanner = sub_scanners.remove(0);
+				if let Some(progress) = scan_settings.progress.as_ref().filter(|_| is_root) {
+					progress.store(((self.sub_scanner_count - sub_scanners.len()) * 100 / self.sub_scanner_count) as u32, Ordering::SeqCst);
+				}
+				if scan_settings.post_order && scan_settings.include_dirs && finished_scanner.accepted && scan_settings.accepts(&finished_scanner.dir) {
+					return Some(finished_scanner.dir);
+				}
+			}
+		}
+
+		// Try Self after contents.
+		if is_root && scan_settings.post_order {
+			if let Some(result) = self.get_self(scan_settings) {
+				return Some(result);
 			}
 		}
 
 		// None found.
+		if let Some(progress) = scan_settings.progress.as_ref().filter(|_| is_root) {
+			progress.store(100, Ordering::SeqCst);
+		}
 		None
 	}
 
-	/// Get all files and folders in the given directory non-recursive.
-	fn get_dir_raw_entries(dir:&FileRef) -> Vec<FileRef> {
+	/// Get all matched entries in the given dir and its sub-dirs, scanning the sub-dirs in parallel.
+	#[cfg(feature="rayon")]
+	fn scan_parallel(dir:&FileRef, depth:usize, scan_settings:&ParallelScanSettings) -> Vec<FileRef> {
+		use rayon::iter::{ IntoParallelRefIterator, ParallelIterator };
+
+		if scan_settings.cancel_token.as_ref().is_some_and(|token| token.is_cancelled()) {
+			return Vec::new();
+		}
+		let (dirs, files):(Vec<RawEntry>, Vec<RawEntry>) = Self::get_dir_raw_entries(dir, None).into_iter().partition(|entry| entry.is_dir);
+		let mut results:Vec<FileRef> = Vec::new();
+		if scan_settings.include_files {
+			results.extend(files.into_iter().filter(|file| (scan_settings.results_filter)(&file.file)).map(|file| file.file));
+		}
+		if scan_settings.include_dirs {
+			results.extend(dirs.iter().filter(|dir| (scan_settings.results_filter)(&dir.file)).map(|dir| dir.file.clone()));
+		}
+		let may_recurse:bool = scan_settings.max_depth.is_none_or(|max_depth| depth < max_depth);
+		let sub_dirs:Vec<&RawEntry> = dirs.iter().filter(|dir| may_recurse && (scan_settings.follow_symlinks || !dir.is_symlink) && (scan_settings.recurse_filter)(&dir.file)).collect();
+		results.extend(sub_dirs.par_iter().flat_map_iter(|sub_dir| Self::scan_parallel(&sub_dir.file, depth + 1, scan_settings)).collect::<Vec<FileRef>>());
+		results
+	}
+
+	/// Count all matched entries in the given dir and its sub-dirs. Only builds a FileRef for an entry when a filter has to run on it.
+	fn count_matching(dir:&Path, depth:usize, scan_settings:&ScanSettings) -> usize {
+		if scan_settings.cancel_token.as_ref().is_some_and(|token| token.is_cancelled()) {
+			return 0;
+		}
+		let Ok(dir_entries) = std::fs::read_dir(dir) else {
+			return 0;
+		};
+		let may_recurse:bool = scan_settings.recurse && scan_settings.max_depth.is_none_or(|max_depth| depth < max_depth);
+		let mut count:usize = 0;
+		for dir_entry in dir_entries.flatten() {
+			let entry_path:PathBuf = dir_entry.path();
+			let Some(entry_path_str) = entry_path.to_str() else {
+				continue; // Skipped like in the scan itself, as the path can not be a FileRef.
+			};
+			let entry_file:OnceCell<FileRef> = OnceCell::new();
+			let entry_file = || entry_file.get_or_init(|| FileRef::new(entry_path_str));
+			let (is_dir, is_symlink, metadata) = Self::classify_entry(&dir_entry, scan_settings.metadata_filter.is_some());
+			let accepted:bool = match (&scan_settings.metadata_filter, metadata) {
+				(Some(metadata_filter), Some(metadata)) => metadata_filter(entry_file(), &metadata),
+				(Some(_), None) => false,
+				(None, _) => true
+			};
+			let included:bool = if is_dir { scan_settings.include_dirs } else { scan_settings.include_files };
+			if included && accepted && (scan_settings.results_filter.is_none() || scan_settings.accepts(entry_file())) {
+				count += 1;
+			}
+			if is_dir && may_recurse && (scan_settings.follow_symlinks || !is_symlink) && (scan_settings.recurse_filter.is_none() || scan_settings.recurses_into(entry_file())) {
+				count += Self::count_matching(&entry_path, depth + 1, scan_settings);
+			}
+		}
+		count
+	}
+
+	/// Get the scanner's own dir if it should be in the results and has not been returned yet. A root that is a file has no entries, so it is also returned when including files.
+	fn get_self(&mut self, scan_settings:&ScanSettings) -> Option<FileRef> {
+		if self.parsed_self {
+			return None;
+		}
+		self.parsed_self = true;
+		let root_is_file:bool = self.dir.exists() && self.dir.is_file();
+		let metadata_accepted = || match &scan_settings.metadata_filter {
+			Some(metadata_filter) => std::fs::metadata(self.dir.path()).is_ok_and(|metadata| metadata_filter(&self.dir, &metadata)),
+			None => true
+		};
+		if (scan_settings.include_self || (root_is_file && scan_settings.include_files)) && scan_settings.accepts(&self.dir) && metadata_accepted() {
+			Some(self.dir.clone())
+		} else {
+			None
+		}
+	}
+
+	/// Get all files and folders in the given directory non-recursive. Symlinks are classified by the type of their target, broken symlinks count as files. Entries of which the path is not valid UTF-8 are skipped, as they can not be a FileRef.
+	fn get_dir_raw_entries(dir:&FileRef, metadata_filter:Option<&MetadataFilter>) -> Vec<RawEntry> {
 		std::fs::read_dir(dir.path())
 			.map(|results|
 				results
 					.flatten()
-					.map(|dir_entry|
-						FileRef::new(dir_entry.path().to_str().unwrap())
-					)
-					.collect::<Vec<FileRef>>()
+					.filter_map(|dir_entry| {
+						let file:FileRef = FileRef::new(dir_entry.path().to_str()?);
+						let (is_dir, is_symlink, metadata) = Self::classify_entry(&dir_entry, metadata_filter.is_some());
+						let accepted:bool = match (metadata_filter, metadata) {
+							(Some(metadata_filter), Some(metadata)) => metadata_filter(&file, &metadata),
+							(Some(_), None) => false,
+							(None, _) => true
+						};
+						Some(RawEntry { file, is_dir, is_symlink, accepted })
+					})
+					.collect::<Vec<RawEntry>>()
 			).unwrap_or_default()
 	}
+
+	/// Classify a dir entry as dir or not and as symlink or not. Symlinks are classified by the type of their target, broken symlinks count as files. When fetching metadata, the metadata is returned as well, entries of which the metadata can not be read count as files.
+	fn classify_entry(dir_entry:&DirEntry, fetch_metadata:bool) -> (bool, bool, Option<Metadata>) {
+		let is_symlink:bool = dir_entry.file_type().is_ok_and(|file_type| file_type.is_symlink());
+		if fetch_metadata {
+			let metadata:Option<Metadata> = Self::get_entry_metadata(dir_entry, is_symlink);
+			(metadata.as_ref().is_some_and(|metadata| metadata.is_dir()), is_symlink, metadata)
+		} else {
+			let is_dir:bool = match dir_entry.file_type() {
+				Ok(_) if is_symlink => std::fs::metadata(dir_entry.path()).map(|metadata| metadata.is_dir()).unwrap_or(false),
+				Ok(file_type) => file_type.is_dir(),
+				Err(_) => false
+			};
+			(is_dir, is_symlink, None)
+		}
+	}
+
+	/// Get the metadata of a dir entry, following symlinks.
+	fn get_entry_metadata(dir_entry:&DirEntry, is_symlink:bool) -> Option<Metadata> {
+		#[cfg(test)]
+		crate::unit_test_support::record_syscall(crate::unit_test_support::Syscall::EntryMetadata);
+		if is_symlink {
+			std::fs::metadata(dir_entry.path()).ok()
+		} else {
+			dir_entry.metadata().ok()
+		}
+	}
+}
+
+
+
+#[cfg(feature="rayon")]
+struct ParallelScanSettings<'a> {
+	include_files:bool,
+	include_dirs:bool,
+	follow_symlinks:bool,
+	max_depth:Option<usize>,
+	cancel_token:Option<CancelToken>,
+	results_filter:&'a (dyn Fn(&FileRef) -> bool + Sync),
+	recurse_filter:&'a (dyn Fn(&FileRef) -> bool + Sync)
+}
+
+
+
+struct RawEntry {
+	file:FileRef,
+	is_dir:bool,
+	is_symlink:bool,
+	accepted:bool
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-	use crate::{ FileRef, FileScanner, unit_test_support::TempFile };
+	use std::{ collections::HashMap, sync::{ Arc, atomic::{ AtomicU32, Ordering } } };
+	use crate::{ CancelToken, FileErrors, FileRef, FileScanner, FileTree, unit_test_support::{ Syscall, TempFile, count_syscalls } };
 
 
 
@@ -59,6 +60,33 @@ mod tests {
 		assert!(results.iter().any(|e| !e.is_dir()));
 	}
 
+	#[test]
+	fn test_post_order() {
+		let temp_file:TempFile = create_test_structure();
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let results:Vec<FileRef> = FileScanner::new(&temp_file_ref).include_self().include_files().include_dirs().recurse().post_order().collect();
+		assert_eq!(results.len(), 8);
+
+		// Every entry inside a dir should come before the dir itself.
+		for (dir_index, dir) in results.iter().enumerate().filter(|(_, entry)| entry.is_dir()) {
+			let dir_prefix:String = dir.path().to_owned() + "/";
+			for (entry_index, entry) in results.iter().enumerate() {
+				if entry.path().starts_with(&dir_prefix) {
+					assert!(entry_index < dir_index, "{entry} was yielded after its parent {dir}");
+				}
+			}
+		}
+		assert_eq!(results.last(), Some(&temp_file_ref));
+	}
+
+	#[test]
+	fn test_post_order_without_recursion() {
+		let temp_file:TempFile = create_test_structure();
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let results:Vec<FileRef> = FileScanner::new(&temp_file_ref).include_dirs().post_order().collect();
+		assert_eq!(results.len(), 2);
+	}
+
 	#[test]
 	fn test_filter() {
 		let temp_file:TempFile = create_test_structure();
@@ -69,6 +97,22 @@ mod tests {
 		assert_eq!(results.len(), 4);
 	}
 
+	#[test]
+	#[cfg(feature="regex")]
+	fn test_name_regex() {
+		let temp_file:TempFile = create_test_structure();
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		(temp_file_ref.clone() + "/subdir1/file_5.txt").create().unwrap();
+		(temp_file_ref.clone() + "/subdir2/notes.md").create().unwrap();
+
+		let scanner:FileScanner = FileScanner::new(&temp_file_ref).include_files().recurse().name_regex(r"^file\d+\.txt$").unwrap();
+		let mut names:Vec<String> = scanner.map(|f| f.name().to_string()).collect();
+		names.sort();
+		assert_eq!(names, vec!["file1.txt", "file2.txt", "file3.txt", "file4.txt"]);
+
+		assert!(FileScanner::new(&temp_file_ref).name_regex(r"^file(\d+$").is_err());
+	}
+
 	#[test]
 	fn test_recursion() {
 		let temp_file:TempFile = create_test_structure();
@@ -96,4 +140,374 @@ mod tests {
 		assert!(results.iter().all(|f| f.name().ends_with("1.txt")));
 		assert_eq!(results.len(), 0);
 	}
+
+	#[test]
+	fn test_root_is_existing_file() {
+		let temp_file:TempFile = create_test_structure();
+		let file_ref:FileRef = FileRef::new(&(temp_file.path().to_owned() + "/file1.txt"));
+
+		let results:Vec<FileRef> = FileScanner::new(&file_ref).include_self().collect();
+		assert_eq!(results, vec![file_ref.clone()]);
+
+		let results:Vec<FileRef> = FileScanner::new(&file_ref).include_files().recurse().collect();
+		assert_eq!(results, vec![file_ref.clone()]);
+
+		let results:Vec<FileRef> = FileScanner::new(&file_ref).include_dirs().collect();
+		assert_eq!(results.len(), 0);
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_symlink_classification() {
+		use std::os::unix::fs::symlink;
+
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path()).absolute();
+		symlink((root.clone() + "/file1.txt").path(), (root.clone() + "/link_to_file").path()).unwrap();
+		symlink((root.clone() + "/subdir2").path(), (root.clone() + "/link_to_dir.txt").path()).unwrap();
+
+		let files:Vec<FileRef> = FileScanner::new(&root).include_files().collect();
+		assert!(files.contains(&(root.clone() + "/link_to_file")));
+		assert!(!files.contains(&(root.clone() + "/link_to_dir.txt")));
+
+		let dirs:Vec<FileRef> = FileScanner::new(&root).include_dirs().collect();
+		assert!(dirs.contains(&(root.clone() + "/link_to_dir.txt")));
+		assert!(!dirs.contains(&(root.clone() + "/link_to_file")));
+	}
+
+	#[test]
+	fn test_unique_by_content() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		(root.clone() + "/file1.txt").write("duplicate".to_string()).unwrap();
+		(root.clone() + "/subdir1/file2.txt").write("duplicate".to_string()).unwrap();
+		(root.clone() + "/subdir2/file4.txt").write("unique".to_string()).unwrap();
+		(root.clone() + "/subdir1/sub_subdir1/file3.txt").write("duplicat".to_string()).unwrap();
+
+		let results:Vec<FileRef> = FileScanner::new(&root).include_files().include_dirs().recurse().unique_by_content().unwrap();
+		let names:Vec<&str> = results.iter().map(|file| file.name()).collect();
+		assert_eq!(names, vec!["file1.txt", "file3.txt", "file4.txt"]);
+
+		// Files of the same size are only discarded when their bytes match.
+		(root.clone() + "/subdir2/file4.txt").write("duplicatf".to_string()).unwrap();
+		let results:Vec<FileRef> = FileScanner::new(&root).include_files().recurse().unique_by_content().unwrap();
+		let names:Vec<&str> = results.iter().map(|file| file.name()).collect();
+		assert_eq!(names, vec!["file1.txt", "file3.txt", "file4.txt"]);
+	}
+
+	#[test]
+	fn test_group_by_parent() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		let groups:HashMap<FileRef, Vec<FileRef>> = FileScanner::new(&root).include_files().recurse().group_by_parent();
+
+		assert_eq!(groups.len(), 4);
+		assert_eq!(groups[&root], vec![root.clone() + "/file1.txt"]);
+		assert_eq!(groups[&(root.clone() + "/subdir1")], vec![root.clone() + "/subdir1/file2.txt"]);
+		assert_eq!(groups[&(root.clone() + "/subdir1/sub_subdir1")], vec![root.clone() + "/subdir1/sub_subdir1/file3.txt"]);
+		assert_eq!(groups[&(root.clone() + "/subdir2")], vec![root.clone() + "/subdir2/file4.txt"]);
+	}
+
+	#[test]
+	fn test_into_tree() {
+		fn tree_names(tree:&FileTree) -> String {
+			let children:Vec<String> = tree.children.iter().map(tree_names).collect();
+			if children.is_empty() { tree.file.name().to_string() } else { format!("{}[{}]", tree.file.name(), children.join(",")) }
+		}
+
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path()).absolute();
+
+		let tree:FileTree = FileScanner::new(&root).include_files().recurse().into_tree();
+		assert_eq!(tree.file, root);
+		assert_eq!(tree_names(&tree), format!("{}[file1.txt,subdir1[file2.txt,sub_subdir1[file3.txt]],subdir2[file4.txt]]", root.name()));
+
+		let tree:FileTree = FileScanner::new(&root).include_files().recurse().filter(|file| file.name() == "file3.txt").into_tree();
+		assert_eq!(tree_names(&tree), format!("{}[subdir1[sub_subdir1[file3.txt]]]", root.name()));
+
+		let tree:FileTree = FileScanner::new(&root).include_dirs().post_order().recurse().into_tree();
+		assert_eq!(tree_names(&tree), format!("{}[subdir1[sub_subdir1],subdir2]", root.name()));
+	}
+
+	#[test]
+	fn test_relative_to_root() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+
+		let mut results:Vec<FileRef> = FileScanner::new(&root).include_files().recurse().relative_to_root().collect();
+		results.sort_by(|a, b| a.path().cmp(b.path()));
+		assert_eq!(results, vec![
+			FileRef::new("file1.txt"),
+			FileRef::new("subdir1/file2.txt"),
+			FileRef::new("subdir1/sub_subdir1/file3.txt"),
+			FileRef::new("subdir2/file4.txt")
+		]);
+	}
+
+	#[test]
+	fn test_with_cancel() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		let token:CancelToken = CancelToken::new();
+
+		let mut scanner:FileScanner = FileScanner::new(&root).include_files().include_dirs().recurse().with_cancel(token.clone());
+		assert!(scanner.next().is_some());
+		assert!(scanner.next().is_some());
+		let thread_token:CancelToken = token.clone();
+		std::thread::spawn(move || thread_token.cancel()).join().unwrap();
+		assert!(token.is_cancelled());
+		assert!(scanner.next().is_none());
+		assert!(scanner.next().is_none());
+
+		assert_eq!(FileScanner::new(&root).include_files().include_dirs().recurse().with_cancel(CancelToken::new()).count(), 7);
+	}
+
+	#[test]
+	fn test_max_depth() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		let names = |max_depth:usize| -> Vec<String> {
+			let mut names:Vec<String> = FileScanner::new(&root).include_files().include_dirs().recurse().max_depth(max_depth).map(|entry| entry.name().to_string()).collect();
+			names.sort();
+			names
+		};
+
+		assert_eq!(names(0), vec!["file1.txt", "subdir1", "subdir2"]);
+		assert_eq!(names(1), vec!["file1.txt", "file2.txt", "file4.txt", "sub_subdir1", "subdir1", "subdir2"]);
+		assert_eq!(names(2).len(), 7);
+		assert_eq!(FileScanner::new(&root).include_dirs().post_order().recurse().max_depth(0).count(), 2);
+	}
+
+	#[test]
+	fn test_sorted() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		(root.clone() + "/a_file.txt").create().unwrap();
+		(root.clone() + "/z_dir").create().unwrap();
+
+		let names:Vec<String> = FileScanner::new(&root).include_files().include_dirs().recurse().sorted().map(|entry| entry.name().to_string()).collect();
+		assert_eq!(names, vec!["a_file.txt", "file1.txt", "subdir1", "subdir2", "z_dir", "file2.txt", "sub_subdir1", "file3.txt", "file4.txt"]);
+	}
+
+	#[test]
+	fn test_filter_metadata() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		(root.clone() + "/file1.txt").write("large content".to_string()).unwrap();
+		(root.clone() + "/subdir1/sub_subdir1/file3.txt").write("larger content".to_string()).unwrap();
+
+		let mut names:Vec<String> = Vec::new();
+		let fetch_count:usize = count_syscalls(Syscall::EntryMetadata, || {
+			names = FileScanner::new(&root).include_files().include_dirs().recurse().filter_metadata(|_, metadata| metadata.is_file() && metadata.len() > 10).map(|entry| entry.name().to_string()).collect();
+		});
+		names.sort();
+		assert_eq!(names, vec!["file1.txt", "file3.txt"]);
+		assert_eq!(fetch_count, 7); // One per entry in the structure.
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_follow_symlinks() {
+		use std::os::unix::fs::symlink;
+
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path()).absolute();
+		symlink(root.path(), (root.clone() + "/subdir2/loop").path()).unwrap();
+
+		let results:Vec<FileRef> = FileScanner::new(&root).include_files().include_dirs().recurse().collect();
+		assert_eq!(results.len(), 8);
+		assert!(results.contains(&(root.clone() + "/subdir2/loop")));
+
+		let results:Vec<FileRef> = FileScanner::new(&(root.clone() + "/subdir1")).include_files().recurse().follow_symlinks(true).collect();
+		assert_eq!(results.len(), 2);
+		symlink((root.clone() + "/subdir2").path(), (root.clone() + "/subdir1/link").path()).unwrap();
+		let results:Vec<FileRef> = FileScanner::new(&(root.clone() + "/subdir1")).include_files().recurse().max_depth(2).follow_symlinks(true).collect();
+		assert!(results.contains(&(root.clone() + "/subdir1/link/file4.txt")));
+		let results:Vec<FileRef> = FileScanner::new(&(root.clone() + "/subdir1")).include_files().recurse().collect();
+		assert!(!results.contains(&(root.clone() + "/subdir1/link/file4.txt")));
+	}
+
+	#[test]
+	fn test_replace_in_all() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		(root.clone() + "/file1.txt").write("use old_name; old_name();".to_string()).unwrap();
+		(root.clone() + "/subdir1/file2.txt").write("old_name".to_string()).unwrap();
+		(root.clone() + "/subdir2/file4.txt").write("unrelated".to_string()).unwrap();
+		(root.clone() + "/subdir2/binary.bin").write_bytes(b"old_name\0\x01").unwrap();
+
+		assert_eq!(FileScanner::new(&root).include_files().recurse().replace_in_all("old_name", "new_name").unwrap(), 2);
+		assert_eq!((root.clone() + "/file1.txt").read().unwrap(), "use new_name; new_name();");
+		assert_eq!((root.clone() + "/subdir1/file2.txt").read().unwrap(), "new_name");
+		assert_eq!((root.clone() + "/subdir2/file4.txt").read().unwrap(), "unrelated");
+		assert_eq!((root.clone() + "/subdir2/binary.bin").read_bytes().unwrap(), b"old_name\0\x01");
+		assert_eq!(FileScanner::new(&root).include_files().recurse().replace_in_all("old_name", "new_name").unwrap(), 0);
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_replace_in_all_errors() {
+		use std::os::unix::fs::symlink;
+
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path()).absolute();
+		(root.clone() + "/file1.txt").write("old_name".to_string()).unwrap();
+		symlink("missing.txt", (root.clone() + "/broken_link.txt").path()).unwrap();
+
+		let errors:FileErrors = FileScanner::new(&root).include_files().recurse().replace_in_all("old_name", "new_name").unwrap_err();
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].0, root.clone() + "/broken_link.txt");
+		assert_eq!((root.clone() + "/file1.txt").read().unwrap(), "new_name");
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_symlinks() {
+		use std::os::unix::fs::symlink;
+
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path()).absolute();
+		symlink((root.clone() + "/file1.txt").path(), (root.clone() + "/subdir1/link_to_file").path()).unwrap();
+		symlink("../subdir2", (root.clone() + "/subdir1/link_to_dir").path()).unwrap();
+		symlink("missing.txt", (root.clone() + "/broken_link").path()).unwrap();
+
+		let mut results:Vec<(FileRef, FileRef)> = FileScanner::new(&root).include_files().include_dirs().recurse().symlinks().collect();
+		results.sort_by(|a, b| a.0.path().cmp(b.0.path()));
+		assert_eq!(results, vec![
+			(root.clone() + "/broken_link", root.clone() + "/missing.txt"),
+			(root.clone() + "/subdir1/link_to_dir", root.clone() + "/subdir2"),
+			(root.clone() + "/subdir1/link_to_file", root.clone() + "/file1.txt")
+		]);
+		assert_eq!(FileScanner::new(&root).include_dirs().recurse().symlinks().count(), 1);
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_symlinks_keeps_filter() {
+		use std::os::unix::fs::symlink;
+
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path()).absolute();
+		symlink((root.clone() + "/file1.txt").path(), (root.clone() + "/link_to_file").path()).unwrap();
+		symlink((root.clone() + "/file1.txt").path(), (root.clone() + "/other.txt").path()).unwrap();
+
+		let results:Vec<(FileRef, FileRef)> = FileScanner::new(&root).include_files().filter(|file| file.name().starts_with("link")).symlinks().collect();
+		assert_eq!(results, vec![(root.clone() + "/link_to_file", root.clone() + "/file1.txt")]);
+	}
+
+	#[test]
+	fn test_with_progress() {
+		let temp_file:TempFile = create_test_structure();
+		let progress:Arc<AtomicU32> = Arc::new(AtomicU32::new(0));
+		let mut scanner:FileScanner = FileScanner::new(&FileRef::new(temp_file.path())).include_files().recurse().sorted().with_progress(progress.clone());
+
+		let mut observed_progress:Vec<u32> = Vec::new();
+		while scanner.next().is_some() {
+			observed_progress.push(progress.load(Ordering::SeqCst));
+		}
+		assert_eq!(observed_progress, vec![0, 0, 0, 50]); // file1, file2 and file3 are found before subdir1 is done, file4 after.
+		assert_eq!(progress.load(Ordering::SeqCst), 100);
+	}
+
+	#[test]
+	#[cfg(feature="rayon")]
+	fn test_collect_parallel() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		for index in 0..20 {
+			(root.clone() + &format!("/subdir2/extra{index}.txt")).create().unwrap();
+		}
+		let results_filter = |entry:&FileRef| !entry.name().starts_with("extra1");
+		let recurse_filter = |dir:&FileRef| dir.name() != "sub_subdir1";
+
+		let mut sequential:Vec<FileRef> = FileScanner::new(&root).include_files().include_dirs().recurse_filter(recurse_filter).filter(results_filter).collect();
+		let mut parallel:Vec<FileRef> = FileScanner::new(&root).include_files().include_dirs().collect_parallel(results_filter, recurse_filter).unwrap();
+		sequential.sort();
+		parallel.sort();
+		assert_eq!(parallel, sequential);
+		assert!(!parallel.iter().any(|entry| entry.name() == "file3.txt"));
+		assert_eq!(FileScanner::new(&root).include_self().include_files().collect_parallel(|_| true, |_| true).unwrap().len(), 25);
+
+		// Filters set on the scanner itself can not be shared between threads.
+		assert!(FileScanner::new(&root).include_files().filter(|_| true).collect_parallel(|_| true, |_| true).is_err());
+	}
+
+	#[test]
+	#[cfg(feature="rayon")]
+	fn test_par_threads() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		for index in 0..20 {
+			(root.clone() + &format!("/subdir{}/extra{index}.txt", index % 2 + 1)).create().unwrap();
+		}
+		let scan = |thread_count:usize| {
+			let mut results:Vec<FileRef> = FileScanner::new(&root).include_files().include_dirs().par_threads(thread_count).collect_parallel(|_| true, |_| true).unwrap();
+			results.sort();
+			results
+		};
+
+		let single_threaded:Vec<FileRef> = scan(1);
+		assert_eq!(single_threaded.len(), 27);
+		assert_eq!(scan(4), single_threaded);
+	}
+
+	#[test]
+	fn test_count_matching() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		let scanners:Vec<Box<dyn Fn() -> FileScanner>> = vec![
+			Box::new(|| FileScanner::new(&root).include_files()),
+			Box::new(|| FileScanner::new(&root).include_self().include_files().include_dirs().recurse()),
+			Box::new(|| FileScanner::new(&root).include_dirs().recurse().max_depth(0)),
+			Box::new(|| FileScanner::new(&root).include_files().recurse_filter(|dir| dir.name() != "subdir1").filter(|file| file.name() != "file1.txt"))
+		];
+		for scanner in scanners {
+			assert_eq!(scanner().count_matching(), scanner().collect::<Vec<FileRef>>().len());
+		}
+		assert_eq!(FileScanner::new(&root).include_files().recurse().filter(|file| file.extension() == Some("txt")).count_matching(), 4);
+	}
+
+	#[test]
+	fn test_filter_not_thread_safe() {
+		use std::{ cell::Cell, rc::Rc };
+
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		let seen:Rc<Cell<usize>> = Rc::new(Cell::new(0));
+		let seen_in_filter:Rc<Cell<usize>> = seen.clone();
+		let results:Vec<FileRef> = FileScanner::new(&root).include_files().recurse().filter(move |_| { seen_in_filter.set(seen_in_filter.get() + 1); true }).collect();
+		assert_eq!(seen.get(), results.len());
+	}
+
+	#[test]
+	#[cfg(target_os="linux")]
+	fn test_non_utf8_entries_skipped() {
+		use std::{ ffi::OsStr, os::unix::ffi::OsStrExt, path::Path };
+
+		let temp_dir:TempFile = TempFile::new(None);
+		let root:FileRef = FileRef::new(temp_dir.path());
+		(root.clone() + "/file.txt").write("content".to_string()).unwrap();
+		std::fs::write(Path::new(root.path()).join(OsStr::from_bytes(b"invalid_\xFF.txt")), "content").unwrap();
+
+		assert_eq!(FileScanner::new(&root).include_files().collect::<Vec<FileRef>>(), vec![root.clone() + "/file.txt"]);
+		assert_eq!(FileScanner::new(&root).include_files().count_matching(), 1);
+		assert_eq!(FileScanner::new(&root).include_files().filter(|file| file.name() == "file.txt").count_matching(), 1);
+	}
+
+	#[test]
+	fn test_sample() {
+		let temp_file:TempFile = create_test_structure();
+		let root:FileRef = FileRef::new(temp_file.path());
+		for index in 0..30 {
+			(root.clone() + &format!("/subdir2/extra{index}.txt")).create().unwrap();
+		}
+		let scanner = || FileScanner::new(&root).include_files().recurse().sorted();
+
+		let sample:Vec<FileRef> = scanner().sample(5, 42);
+		assert_eq!(sample.len(), 5);
+		assert_eq!(sample, scanner().sample(5, 42));
+		assert_ne!(scanner().sample(5, 42), scanner().sample(5, 43));
+		assert_eq!(scanner().sample(100, 42).len(), 34);
+		assert_eq!(scanner().sample(0, 42).len(), 0);
+	}
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+use std::{ error::Error, ops::Deref };
+use crate::FileRef;
+
+
+
+pub struct FileTransaction {
+	target:FileRef,
+	working_copy:FileRef,
+	finished:bool
+}
+impl FileTransaction {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Start a transaction on the given file. Creates a hidden working copy next to the file that all changes are made to. A symlink is resolved, so the transaction edits the file it points to and keeps the symlink.
+	pub fn new(target:&FileRef) -> Result<FileTransaction, Box<dyn Error>> {
+		if target.is_dir() {
+			return Err(format!("Could not start transaction on dir \"{}\". Only able to edit files.", target.path()).into());
+		}
+		if target.is_symlink() {
+			return FileTransaction::new(&target.canonicalize()?);
+		}
+		let working_copy:FileRef = target.parent_dir()? + &format!("/.{}.transaction", target.name());
+		if working_copy.exists() {
+			return Err(format!("Could not start transaction on file \"{}\". Another transaction is in progress.", target.path()).into());
+		}
+		if target.exists() {
+			target.copy_to(&working_copy)?;
+		} else {
+			working_copy.create_file()?;
+		}
+		Ok(FileTransaction {
+			target: target.clone(),
+			working_copy,
+			finished: false
+		})
+	}
+
+
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the file the transaction will replace on commit. For a symlink this is the file it points to.
+	pub fn target(&self) -> &FileRef {
+		&self.target
+	}
+
+
+
+	/* USAGE METHODS */
+
+	/// Replace the target file with the working copy in a single rename. When the rename fails, the working copy is removed like on rollback.
+	pub fn commit(mut self) -> Result<(), Box<dyn Error>> {
+		std::fs::rename(self.working_copy.path(), self.target.path())?;
+		self.finished = true;
+		Ok(())
+	}
+
+	/// Discard all changes, leaving the target file untouched. Dropping the transaction does the same.
+	pub fn rollback(mut self) -> Result<(), Box<dyn Error>> {
+		self.finished = true;
+		self.working_copy.delete()
+	}
+}
+impl Deref for FileTransaction {
+	type Target = FileRef;
+
+	fn deref(&self) -> &Self::Target {
+		&self.working_copy
+	}
+}
+impl Drop for FileTransaction {
+	fn drop(&mut self) {
+		if !self.finished && self.working_copy.exists() {
+			let _ = self.working_copy.delete();
+		}
+	}
+}
\ No newline at end of file
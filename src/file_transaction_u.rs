@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ FileRef, FileTransaction, unit_test_support::TempFile };
+
+
+
+	#[test]
+	fn test_transaction_commit() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Hello".to_string()).unwrap();
+
+		let transaction:FileTransaction = temp_file_ref.transaction().unwrap();
+		transaction.append(", world".to_string()).unwrap();
+		transaction.append("!".to_string()).unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Hello");
+
+		transaction.commit().unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Hello, world!");
+		assert_eq!(temp_file_ref.parent_dir().unwrap().list_files().iter().filter(|file| file.name().contains(".transaction")).count(), 0);
+	}
+
+	#[test]
+	fn test_transaction_rollback() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Original".to_string()).unwrap();
+
+		// Explicit rollback.
+		let transaction:FileTransaction = temp_file_ref.transaction().unwrap();
+		transaction.write("Changed".to_string()).unwrap();
+		transaction.rollback().unwrap();
+		assert_eq!(temp_file_ref.read().unwrap(), "Original");
+
+		// Rollback on drop.
+		let transaction:FileTransaction = temp_file_ref.transaction().unwrap();
+		transaction.write("Changed".to_string()).unwrap();
+		let working_copy:FileRef = (*transaction).clone();
+		drop(transaction);
+		assert_eq!(temp_file_ref.read().unwrap(), "Original");
+		assert!(!working_copy.exists());
+	}
+
+	#[test]
+	fn test_transaction_failed_commit() {
+		let temp_dir:TempFile = TempFile::new(None);
+		let target:FileRef = FileRef::new(temp_dir.path()) + "/config.txt";
+		target.write("Original".to_string()).unwrap();
+
+		// Replace the target by a non-empty dir, so the rename can not replace it.
+		let transaction:FileTransaction = target.transaction().unwrap();
+		let working_copy:FileRef = (*transaction).clone();
+		transaction.write("Changed".to_string()).unwrap();
+		std::fs::remove_file(target.path()).unwrap();
+		std::fs::create_dir(target.path()).unwrap();
+		std::fs::write((target.clone() + "/blocker.txt").path(), "T").unwrap();
+		assert!(transaction.commit().is_err());
+		assert!(!working_copy.exists());
+	}
+
+	#[test]
+	#[cfg(unix)]
+	fn test_transaction_symlink() {
+		use std::os::unix::fs::symlink;
+
+		let temp_dir:TempFile = TempFile::new(None);
+		let target:FileRef = FileRef::new(temp_dir.path()) + "/config.txt";
+		let link:FileRef = FileRef::new(temp_dir.path()) + "/link.txt";
+		target.write("Original".to_string()).unwrap();
+		symlink("config.txt", link.path()).unwrap();
+
+		let transaction:FileTransaction = link.transaction().unwrap();
+		transaction.write("Changed".to_string()).unwrap();
+		transaction.commit().unwrap();
+		assert!(link.is_symlink());
+		assert_eq!(target.read().unwrap(), "Changed");
+	}
+}
\ No newline at end of file
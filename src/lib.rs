@@ -1,11 +1,23 @@
+mod cached_file;
+mod cached_file_u;
+mod file_lock;
+mod file_lock_u;
 mod file_ref;
 mod file_ref_u;
 mod file_scanner;
 mod file_scanner_u;
+mod file_transaction;
+mod file_transaction_u;
+mod size_tracker;
+mod size_tracker_u;
 mod unit_test_support;
 
+pub use cached_file::*;
+pub use file_lock::*;
 pub use file_ref::*;
 pub use file_scanner::*;
+pub use file_transaction::*;
+pub use size_tracker::*;
 pub use unit_test_support::*;
 
 #[cfg(feature="dir_monitor")]
@@ -13,4 +25,11 @@ mod dir_monitor;
 #[cfg(feature="dir_monitor")]
 mod dir_monitor_u;
 #[cfg(feature="dir_monitor")]
-pub use dir_monitor::*;
\ No newline at end of file
+pub use dir_monitor::*;
+
+#[cfg(feature="mmap")]
+mod mapped_file;
+#[cfg(feature="mmap")]
+mod mapped_file_u;
+#[cfg(feature="mmap")]
+pub use mapped_file::*;
\ No newline at end of file
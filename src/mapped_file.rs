@@ -0,0 +1,57 @@
+use std::{ error::Error, fs::File };
+use memmap2::Mmap;
+use crate::FileRef;
+
+
+
+pub struct MappedFile {
+	file:FileRef,
+	map:Mmap
+}
+impl MappedFile {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Map the file into memory. The file should not be modified while mapped, as the mapped contents would change along with it.
+	pub fn new(file:&FileRef) -> Result<MappedFile, Box<dyn Error>> {
+		if file.is_dir() {
+			Err(format!("Could not map dir \"{}\". Only able to map files.", file.path()).into())
+		} else if !file.exists() {
+			Err(format!("Could not map file \"{}\". File does not exist.", file.path()).into())
+		} else {
+			let handle:File = File::open(file.path())?;
+			Ok(MappedFile {
+				file: file.clone(),
+				map: unsafe { Mmap::map(&handle)? }
+			})
+		}
+	}
+
+
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the file that is mapped.
+	pub fn file(&self) -> &FileRef {
+		&self.file
+	}
+
+	/// Get the amount of bytes mapped.
+	pub fn len(&self) -> usize {
+		self.map.len()
+	}
+
+	/// Check if the mapped file is empty.
+	pub fn is_empty(&self) -> bool {
+		self.map.is_empty()
+	}
+
+
+
+	/* READING METHODS */
+
+	/// Get a view of a range of bytes in the file without copying them. Panics if the range is out of bounds, like slicing would.
+	pub fn range(&self, start:u64, end:u64) -> &[u8] {
+		&self.map[start as usize..end as usize]
+	}
+}
\ No newline at end of file
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ FileRef, MappedFile, unit_test_support::TempFile };
+
+
+
+	#[test]
+	fn test_mapped_range() {
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		let content:Vec<u8> = (0..4096).map(|index| (index % 251) as u8).collect();
+		temp_file_ref.write_bytes(&content).unwrap();
+
+		let mapped_file:MappedFile = MappedFile::new(&temp_file_ref).unwrap();
+		assert_eq!(mapped_file.len(), content.len());
+		for (start, end) in [(0, 16), (100, 1000), (4000, 4096)] {
+			assert_eq!(mapped_file.range(start, end), temp_file_ref.read_range(start, end).unwrap().as_slice());
+		}
+	}
+
+	#[test]
+	fn test_mapped_missing_file() {
+		let temp_file:TempFile = TempFile::new(Some("bin"));
+		assert!(MappedFile::new(&FileRef::new(temp_file.path())).is_err());
+	}
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+use std::error::Error;
+use crate::FileRef;
+
+
+
+pub struct SizeTracker {
+	file:FileRef,
+	size:u64
+}
+impl SizeTracker {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new size tracker, using the current size of the file as baseline.
+	pub fn new(file:&FileRef) -> Result<SizeTracker, Box<dyn Error>> {
+		Ok(SizeTracker {
+			file: file.clone(),
+			size: Self::current_size(file)?
+		})
+	}
+
+
+
+	/* USAGE METHODS */
+
+	/// Get the amount of bytes the file has grown (positive) or shrunk (negative) since the last check and use the current size as the new baseline.
+	pub fn delta(&mut self) -> Result<i64, Box<dyn Error>> {
+		let size:u64 = Self::current_size(&self.file)?;
+		let delta:i64 = size as i64 - self.size as i64;
+		self.size = size;
+		Ok(delta)
+	}
+
+	/// Get the size of the file, erroring if it does not exist.
+	fn current_size(file:&FileRef) -> Result<u64, Box<dyn Error>> {
+		if file.exists() {
+			Ok(file.bytes_size())
+		} else {
+			Err(format!("Could not get size of file \"{}\". File does not exist.", file.path()).into())
+		}
+	}
+
+
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the file being tracked.
+	pub fn file(&self) -> &FileRef {
+		&self.file
+	}
+
+	/// Get the size of the file at the last check.
+	pub fn size(&self) -> u64 {
+		self.size
+	}
+}
\ No newline at end of file
@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ FileRef, SizeTracker, unit_test_support::TempFile };
+
+
+
+	#[test]
+	fn test_size_tracker_delta() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		let temp_file_ref:FileRef = FileRef::new(temp_file.path());
+		temp_file_ref.write("Hello".to_string()).unwrap();
+
+		let mut tracker:SizeTracker = SizeTracker::new(&temp_file_ref).unwrap();
+		assert_eq!(tracker.size(), 5);
+		assert_eq!(tracker.delta().unwrap(), 0);
+
+		// Grow.
+		temp_file_ref.append(", world!".to_string()).unwrap();
+		assert_eq!(tracker.delta().unwrap(), 8);
+		assert_eq!(tracker.delta().unwrap(), 0);
+
+		// Truncate.
+		temp_file_ref.write("Hi".to_string()).unwrap();
+		assert_eq!(tracker.delta().unwrap(), -11);
+		assert_eq!(tracker.size(), 2);
+	}
+
+	#[test]
+	fn test_size_tracker_missing_file() {
+		let temp_file:TempFile = TempFile::new(Some("txt"));
+		assert!(SizeTracker::new(&FileRef::new(temp_file.path())).is_err());
+	}
+}
\ No newline at end of file
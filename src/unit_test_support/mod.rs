@@ -1,3 +1,7 @@
 mod temp_file;
 mod temp_file_u;
-pub use temp_file::*;
\ No newline at end of file
+#[cfg(test)]
+mod syscall_counter;
+pub use temp_file::*;
+#[cfg(test)]
+pub(crate) use syscall_counter::*;
\ No newline at end of file
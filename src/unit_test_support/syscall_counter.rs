@@ -0,0 +1,24 @@
+use std::cell::Cell;
+
+
+
+#[derive(Clone, Copy)]
+pub(crate) enum Syscall { Stat, EntryMetadata }
+thread_local! {
+	static SYSCALL_COUNTS:[Cell<usize>; 2] = const { [Cell::new(0), Cell::new(0)] };
+}
+
+
+
+/// Register a syscall made by the library on the current thread.
+pub(crate) fn record_syscall(syscall:Syscall) {
+	SYSCALL_COUNTS.with(|counts| counts[syscall as usize].set(counts[syscall as usize].get() + 1));
+}
+
+/// Count how many syscalls of the given kind the current thread makes while running the given action.
+pub(crate) fn count_syscalls<T:FnOnce()>(syscall:Syscall, action:T) -> usize {
+	let count = || SYSCALL_COUNTS.with(|counts| counts[syscall as usize].get());
+	let count_before:usize = count();
+	action();
+	count() - count_before
+}
\ No newline at end of file